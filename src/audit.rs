@@ -0,0 +1,178 @@
+use std::fmt;
+
+use crate::models::Usage;
+use crate::tokenizer::TokenSource;
+
+// Known prompts covering text the character heuristic handles badly.
+pub const AUDIT_PROMPTS: &[(&str, &str)] = &[
+    (
+        "english",
+        "Summarize in two sentences why accurate token accounting matters for API billing.",
+    ),
+    (
+        "code",
+        "Explain what this Rust function does in one sentence:\n\nfn fib(n: u64) -> u64 {\n    match n {\n        0 | 1 => n,\n        _ => fib(n - 1) + fib(n - 2),\n    }\n}",
+    ),
+    (
+        "multilingual",
+        "Traduis en français puis en japonais : « La facturation doit correspondre exactement à l'utilisation. »",
+    ),
+    (
+        "symbols",
+        "Repeat this line once, unchanged: ✅ 42 × π ≈ 131.95 — {\"ok\": true} 🚀",
+    ),
+];
+
+pub const AUDIT_MAX_COMPLETION_TOKENS: u32 = 96;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditMode {
+    Streaming,
+    NonStreaming,
+}
+
+impl fmt::Display for AuditMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditMode::Streaming => write!(f, "streaming"),
+            AuditMode::NonStreaming => write!(f, "non-streaming"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UsageAuditSample {
+    pub prompt_name: &'static str,
+    pub mode: AuditMode,
+    pub local_prompt_tokens: u32,
+    pub local_completion_tokens: u32,
+    pub local_source: TokenSource,
+    // None when the response carried no usage (for streams: no usage chunk arrived)
+    pub usage: Option<Usage>,
+}
+
+impl UsageAuditSample {
+    pub fn prompt_diff(&self) -> Option<i64> {
+        self.usage
+            .as_ref()
+            .map(|u| u.prompt_tokens as i64 - self.local_prompt_tokens as i64)
+    }
+
+    pub fn completion_diff(&self) -> Option<i64> {
+        self.usage
+            .as_ref()
+            .and_then(|u| u.completion_tokens)
+            .map(|ct| ct as i64 - self.local_completion_tokens as i64)
+    }
+}
+
+// A count is flagged when it is off by more than `tolerance_pct` percent and more than
+// two tokens, so tiny prompts don't trip on chat framing differences.
+pub fn is_discrepancy(diff: i64, local: u32, tolerance_pct: f64) -> bool {
+    let abs = diff.unsigned_abs();
+    abs > 2 && (abs as f64) > local.max(1) as f64 * tolerance_pct / 100.0
+}
+
+#[derive(Debug)]
+pub struct UsageAuditStats {
+    pub model: String,
+    pub samples: usize,
+    pub missing_usage_streaming: usize,
+    pub missing_usage_non_streaming: usize,
+    pub prompt_discrepancies: usize,
+    pub completion_discrepancies: usize,
+    pub mean_prompt_diff_pct: f64,
+    pub mean_completion_diff_pct: f64,
+    pub local_source: TokenSource,
+}
+
+impl UsageAuditStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("samples", self.samples as f64),
+            ("missing_usage_streaming", self.missing_usage_streaming as f64),
+            ("missing_usage_non_streaming", self.missing_usage_non_streaming as f64),
+            ("prompt_discrepancies", self.prompt_discrepancies as f64),
+            ("completion_discrepancies", self.completion_discrepancies as f64),
+            ("mean_prompt_diff_pct", self.mean_prompt_diff_pct),
+            ("mean_completion_diff_pct", self.mean_completion_diff_pct),
+        ]
+    }
+}
+
+pub fn summarize(model: &str, samples: &[UsageAuditSample], tolerance_pct: f64) -> UsageAuditStats {
+    let missing = |mode| {
+        samples
+            .iter()
+            .filter(|s| s.mode == mode && s.usage.is_none())
+            .count()
+    };
+
+    let prompt_diffs: Vec<(i64, u32)> = samples
+        .iter()
+        .filter_map(|s| s.prompt_diff().map(|d| (d, s.local_prompt_tokens)))
+        .collect();
+    let completion_diffs: Vec<(i64, u32)> = samples
+        .iter()
+        .filter_map(|s| s.completion_diff().map(|d| (d, s.local_completion_tokens)))
+        .collect();
+
+    let mean_pct = |diffs: &[(i64, u32)]| {
+        if diffs.is_empty() {
+            0.0
+        } else {
+            diffs
+                .iter()
+                .map(|(d, local)| *d as f64 / (*local).max(1) as f64 * 100.0)
+                .sum::<f64>()
+                / diffs.len() as f64
+        }
+    };
+
+    UsageAuditStats {
+        model: model.to_string(),
+        samples: samples.len(),
+        missing_usage_streaming: missing(AuditMode::Streaming),
+        missing_usage_non_streaming: missing(AuditMode::NonStreaming),
+        prompt_discrepancies: prompt_diffs
+            .iter()
+            .filter(|(d, local)| is_discrepancy(*d, *local, tolerance_pct))
+            .count(),
+        completion_discrepancies: completion_diffs
+            .iter()
+            .filter(|(d, local)| is_discrepancy(*d, *local, tolerance_pct))
+            .count(),
+        mean_prompt_diff_pct: mean_pct(&prompt_diffs),
+        mean_completion_diff_pct: mean_pct(&completion_diffs),
+        local_source: samples
+            .first()
+            .map(|s| s.local_source)
+            .unwrap_or(TokenSource::Heuristic),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_differences_are_never_discrepancies() {
+        // 100% off, but within the two-token allowance
+        assert!(!is_discrepancy(2, 2, 5.0));
+        assert!(!is_discrepancy(-2, 2, 5.0));
+        assert!(is_discrepancy(3, 2, 5.0));
+    }
+
+    #[test]
+    fn larger_differences_are_judged_by_the_tolerance() {
+        assert!(!is_discrepancy(5, 100, 5.0));
+        assert!(is_discrepancy(6, 100, 5.0));
+        assert!(is_discrepancy(-6, 100, 5.0));
+        assert!(!is_discrepancy(50, 1000, 5.0));
+    }
+
+    #[test]
+    fn zero_local_counts_use_a_floor_of_one() {
+        assert!(is_discrepancy(3, 0, 5.0));
+    }
+}
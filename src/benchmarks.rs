@@ -1,13 +1,34 @@
 use anyhow::Result;
 use futures::future::join_all;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::Semaphore;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::client::SudoClient;
-use crate::metrics::{MetricsCollector, ThroughputStats};
-use crate::models::ChatCompletionRequest;
+use crate::audit::{self, AuditMode, UsageAuditSample, UsageAuditStats, AUDIT_MAX_COMPLETION_TOKENS, AUDIT_PROMPTS};
+use crate::cache::{self, CacheSample, CacheStats};
+use crate::client::{ClientConfig, ClientPool, ConnectionMode, HttpVersion};
+use crate::connection::{self, ConnectionComparison, ConnectionSample};
+use crate::distributed::{self, AgentResult, RunRequest};
+use crate::conversation::{self, Conversation, ConversationStats, ConversationTurnSample};
+use crate::embeddings::{self, EmbeddingSample, EmbeddingStats};
+use crate::exporter::{self, Exporter};
+use crate::hdr::{self, HdrLog};
+use crate::history::HistoryRecorder;
+use crate::metrics::{GoodputSample, GoodputSlo, MetricsCollector, ThroughputStats};
+use crate::models::{ChatCompletionRequest, EmbeddingRequest};
+use crate::monitor::{self, ModelMonitor, SloConfig};
+use crate::report::{self, ModelReport};
+use crate::server;
+use crate::structured::{self, StructuredSample, StructuredStats};
+use crate::sweep::{self, DecodeSample, DecodeSweepStats, PrefillSample, PrefillSweepStats};
+use crate::tokenizer::TokenizerRegistry;
+use crate::vision::{self, ImageSource, VisionCase, VisionSample, VisionStats};
+use crate::tools::{self, ToolCallSample, ToolCallStats};
 
 #[derive(Debug, Clone)]
 pub struct BenchmarkConfig {
@@ -37,17 +58,155 @@ impl BenchmarkConfig {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct PrefillSweepConfig {
+    pub model: Vec<String>,
+    pub lengths: Vec<u32>,
+    pub requests: usize,
+    pub concurrency: usize,
+    // Overrides for context windows the models endpoint doesn't report
+    pub context_windows: HashMap<String, u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodeSweepConfig {
+    pub model: Vec<String>,
+    pub output_lengths: Vec<u32>,
+    pub requests: usize,
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConversationConfig {
+    pub model: Vec<String>,
+    pub turns: usize,
+    pub conversations: usize,
+    pub concurrency: usize,
+    pub streaming: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub model: Vec<String>,
+    pub prefix_tokens: u32,
+    pub rounds: usize,
+    pub warm_requests: usize,
+    // Pause after the cold request so the provider can finish writing the cache entry
+    pub settle: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolConfig {
+    pub model: Vec<String>,
+    pub requests: usize,
+    pub concurrency: usize,
+    // "auto", "required" or "none", passed through as `tool_choice`
+    pub tool_choice: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructuredConfig {
+    pub model: Vec<String>,
+    pub requests: usize,
+    pub concurrency: usize,
+    // false sends `json_object` with the schema described in the prompt instead
+    pub json_schema: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct VisionConfig {
+    pub model: Vec<String>,
+    // Local image files; when empty, synthetic images at `resolutions` are used
+    pub images: Vec<PathBuf>,
+    pub resolutions: Vec<u32>,
+    pub image_counts: Vec<usize>,
+    pub requests: usize,
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmbeddingConfig {
+    pub model: Vec<String>,
+    pub batch_sizes: Vec<usize>,
+    pub input_tokens: Vec<u32>,
+    pub requests: usize,
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionConfig {
+    pub model: Vec<String>,
+    pub modes: Vec<ConnectionMode>,
+    pub requests: usize,
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProtocolConfig {
+    pub model: Vec<String>,
+    pub versions: Vec<HttpVersion>,
+    pub requests: usize,
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServeMetricsConfig {
+    pub listen: SocketAddr,
+    pub interval: Duration,
+    pub model: Vec<String>,
+    pub requests: usize,
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AgentConfig {
+    pub listen: SocketAddr,
+    // Coordinators must send it as a bearer token
+    pub token: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportConfig {
+    pub model: Vec<String>,
+    pub requests: usize,
+    pub concurrency: usize,
+    pub html: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+    pub interval: Duration,
+    pub model: Vec<String>,
+    pub requests: usize,
+    pub concurrency: usize,
+    pub slo: SloConfig,
+    pub webhook: Option<String>,
+}
+
 pub struct BenchmarkRunner {
-    client: Arc<SudoClient>,
+    clients: Arc<ClientPool>,
     supported_models: Vec<String>,
+    context_windows: HashMap<String, u32>,
+    history: Option<HistoryRecorder>,
+    hdr_log: Option<Arc<HdrLog>>,
+    goodput_slo: Option<GoodputSlo>,
 }
 
 impl BenchmarkRunner {
-    pub async fn new(api_key: String, base_url: String) -> Result<Self> {
-        let client = Arc::new(SudoClient::new(api_key, base_url));
-        
+    pub async fn new(
+        config: ClientConfig,
+        connection_mode: ConnectionMode,
+        tokenizers: Arc<TokenizerRegistry>,
+    ) -> Result<Self> {
+        let clients = Arc::new(ClientPool::new(connection_mode, config, tokenizers));
+
         // Fetch supported models
-        let models_response = client.get_models().await?;
+        let models_response = clients.checkout().get_models().await?;
+        let context_windows: HashMap<String, u32> = models_response
+            .data
+            .iter()
+            .filter_map(|m| m.context_length.map(|len| (m.model_name.clone(), len)))
+            .collect();
         let supported_models: Vec<String> = models_response
             .data
             .into_iter()
@@ -57,11 +216,47 @@ impl BenchmarkRunner {
         info!("Loaded {} supported models", supported_models.len());
 
         Ok(Self {
-            client,
+            clients,
             supported_models,
+            context_windows,
+            history: None,
+            hdr_log: None,
+            goodput_slo: None,
         })
     }
 
+    pub fn with_history(mut self, history: Option<HistoryRecorder>) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn with_hdr_log(mut self, hdr_log: Option<Arc<HdrLog>>) -> Self {
+        self.hdr_log = hdr_log;
+        self
+    }
+
+    pub fn with_goodput_slo(mut self, goodput_slo: Option<GoodputSlo>) -> Self {
+        self.goodput_slo = goodput_slo;
+        self
+    }
+
+    // Keeps a finished test's stats in the history store and its histograms in the HdrHistogram log
+    fn record_results(&self, model: &str, test: &str, metrics: &[(&str, f64)], collector: &MetricsCollector) {
+        if let Some(history) = &self.history {
+            history.record(model, test, metrics, collector);
+        }
+        if let Some(hdr_log) = &self.hdr_log {
+            hdr_log.record(model, collector);
+        }
+    }
+
+    // Stats-only history for benchmarks that don't keep a `MetricsCollector`
+    fn record_stats(&self, model: &str, test: &str, metrics: &[(&str, f64)]) {
+        if let Some(history) = &self.history {
+            history.record_stats(model, test, metrics);
+        }
+    }
+
     pub async fn list_models(&self) -> Result<()> {
         println!("Supported Models:");
         println!("─────────────────");
@@ -71,18 +266,25 @@ impl BenchmarkRunner {
         Ok(())
     }
 
-    pub async fn run_latency_benchmark(&self, config: BenchmarkConfig) -> Result<()> {
-        let models_to_test = if !config.model.is_empty() {
+    // Validate requested models, or fall back to the first `default_limit` supported models
+    // (all of them when no limit is given).
+    fn select_models(&self, requested: &[String], default_limit: Option<usize>) -> Result<Vec<String>> {
+        if !requested.is_empty() {
             // Validate all requested models are supported
-            for model in &config.model {
+            for model in requested {
                 if !self.supported_models.contains(model) {
                     return Err(anyhow::anyhow!("Model '{}' is not supported", model));
                 }
             }
-            config.model.clone()
+            Ok(requested.to_vec())
         } else {
-            self.supported_models.clone()
-        };
+            let limit = default_limit.unwrap_or(self.supported_models.len());
+            Ok(self.supported_models.iter().take(limit).cloned().collect())
+        }
+    }
+
+    pub async fn run_latency_benchmark(&self, config: BenchmarkConfig) -> Result<()> {
+        let models_to_test = self.select_models(&config.model, None)?;
 
         info!("Running latency benchmark on {} models", models_to_test.len());
 
@@ -114,17 +316,32 @@ impl BenchmarkRunner {
     }
 
     async fn run_regular_latency_test(&self, model: &str, requests: usize, concurrency: usize) -> Result<Box<dyn std::fmt::Debug>> {
+        let collector = self.collect_latency_metrics(model, requests, concurrency).await;
+
+        if let Some(stats) = collector.calculate_latency_stats(model) {
+            let mut metrics = stats.history_metrics();
+            metrics.push(("success_rate", collector.success_rate()));
+            self.record_results(model, "latency", &metrics, &collector);
+            Ok(Box::new(stats))
+        } else {
+            Err(anyhow::anyhow!("No successful requests for model {}{}", model, collector.timeout_note()))
+        }
+    }
+
+    // Runs `requests` non-streaming latency requests and collects every outcome
+    async fn collect_latency_metrics(&self, model: &str, requests: usize, concurrency: usize) -> MetricsCollector {
         let semaphore = Arc::new(Semaphore::new(concurrency));
-        let mut collector = MetricsCollector::new();
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
         let mut tasks = Vec::new();
 
         for _ in 0..requests {
-            let client = Arc::clone(&self.client);
+            let clients = Arc::clone(&self.clients);
             let semaphore = Arc::clone(&semaphore);
             let model = model.to_string();
 
             let task = tokio::spawn(async move {
                 let _permit = semaphore.acquire().await.unwrap();
+                let client = clients.checkout();
                 
                 let request = ChatCompletionRequest::benchmark_latency_request(&model, false);
                 client.create_chat_completion(&request).await
@@ -138,30 +355,41 @@ impl BenchmarkRunner {
         for result in results {
             match result {
                 Ok(Ok((_, metric))) => collector.add_latency_metric(metric),
-                Ok(Err(e)) => collector.add_error(e.to_string()),
+                Ok(Err(e)) => collector.add_request_error(&e),
                 Err(e) => collector.add_error(format!("Task error: {}", e)),
             }
         }
 
-        if let Some(stats) = collector.calculate_latency_stats(model) {
+        collector
+    }
+
+    async fn run_streaming_latency_test(&self, model: &str, requests: usize, concurrency: usize) -> Result<Box<dyn std::fmt::Debug>> {
+        let collector = self.collect_streaming_metrics(model, requests, concurrency).await;
+
+        if let Some(stats) = collector.calculate_streaming_stats(model) {
+            let mut metrics = stats.history_metrics();
+            metrics.push(("success_rate", collector.success_rate()));
+            self.record_results(model, "streaming_latency", &metrics, &collector);
             Ok(Box::new(stats))
         } else {
-            Err(anyhow::anyhow!("No successful requests for model {}", model))
+            Err(anyhow::anyhow!("No successful streaming requests for model {}{}", model, collector.timeout_note()))
         }
     }
 
-    async fn run_streaming_latency_test(&self, model: &str, requests: usize, concurrency: usize) -> Result<Box<dyn std::fmt::Debug>> {
+    // Runs `requests` streaming latency requests and collects every outcome
+    async fn collect_streaming_metrics(&self, model: &str, requests: usize, concurrency: usize) -> MetricsCollector {
         let semaphore = Arc::new(Semaphore::new(concurrency));
-        let mut collector = MetricsCollector::new();
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
         let mut tasks = Vec::new();
 
         for _ in 0..requests {
-            let client = Arc::clone(&self.client);
+            let clients = Arc::clone(&self.clients);
             let semaphore = Arc::clone(&semaphore);
             let model = model.to_string();
 
             let task = tokio::spawn(async move {
                 let _permit = semaphore.acquire().await.unwrap();
+                let client = clients.checkout();
                 
                 let request = ChatCompletionRequest::benchmark_latency_request(&model, true);
                 client.create_streaming_chat_completion(&request).await
@@ -177,7 +405,7 @@ impl BenchmarkRunner {
                 Ok(Ok(metric)) => collector.add_streaming_metric(metric),
                 Ok(Err(e)) => {
                     error!("Streaming request failed for model {}: {}", model, e);
-                    collector.add_error(e.to_string());
+                    collector.add_request_error(&e);
                 },
                 Err(e) => {
                     error!("Task error for model {}: {}", model, e);
@@ -186,26 +414,12 @@ impl BenchmarkRunner {
             }
         }
 
-        if let Some(stats) = collector.calculate_streaming_stats(model) {
-            Ok(Box::new(stats))
-        } else {
-            Err(anyhow::anyhow!("No successful streaming requests for model {}", model))
-        }
+        collector
     }
 
     pub async fn run_throughput_benchmark(&self, config: BenchmarkConfig) -> Result<()> {
-        let models_to_test = if !config.model.is_empty() {
-            // Validate all requested models are supported
-            for model in &config.model {
-                if !self.supported_models.contains(model) {
-                    return Err(anyhow::anyhow!("Model '{}' is not supported", model));
-                }
-            }
-            config.model.clone()
-        } else {
-            // For throughput tests, we'll test a subset of models to avoid overwhelming the API
-            self.supported_models.iter().take(5).cloned().collect()
-        };
+        // For throughput tests, we'll test a subset of models to avoid overwhelming the API
+        let models_to_test = self.select_models(&config.model, Some(5))?;
 
         let test_mode = "streaming";
         info!("Running streaming throughput benchmark with {} concurrent requests per model on {} models", 
@@ -235,20 +449,30 @@ impl BenchmarkRunner {
     }
 
     async fn run_streaming_throughput_test(&self, model: &str, concurrency: usize) -> Result<ThroughputStats> {
+        let collector = self.collect_throughput_metrics(model, concurrency).await;
+        let stats = collector.calculate_throughput_stats(model)
+            .ok_or_else(|| anyhow::anyhow!("No successful streaming throughput tests for model {}", model))?;
+        self.record_results(model, "throughput", &stats.history_metrics(), &collector);
+        Ok(stats)
+    }
+
+    // Runs `concurrency` single-request streaming throughput tests at once
+    async fn collect_throughput_metrics(&self, model: &str, concurrency: usize) -> MetricsCollector {
         let semaphore = Arc::new(Semaphore::new(concurrency));
-        let mut collector = MetricsCollector::new();
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
         let mut tasks = Vec::new();
 
         info!("Running {} concurrent single-request streaming throughput tests for model: {}", concurrency, model);
 
         // Each worker makes exactly one streaming request to measure per-request TPS
         for _ in 0..concurrency {
-            let client = Arc::clone(&self.client);
+            let clients = Arc::clone(&self.clients);
             let semaphore = Arc::clone(&semaphore);
             let model = model.to_string();
 
             let task = tokio::spawn(async move {
                 let _permit = semaphore.acquire().await.unwrap();
+                let client = clients.checkout();
                 
                 let request = ChatCompletionRequest::benchmark_throughput_request(&model, true);
                 client.single_request_streaming_throughput_test(&request).await
@@ -262,91 +486,1783 @@ impl BenchmarkRunner {
         for result in results {
             match result {
                 Ok(Ok(metric)) => collector.add_throughput_metric(metric),
-                Ok(Err(e)) => collector.add_error(e.to_string()),
+                Ok(Err(e)) => collector.add_request_error(&e),
                 Err(e) => collector.add_error(format!("Task error: {}", e)),
             }
         }
 
-        collector.calculate_throughput_stats(model)
-            .ok_or_else(|| anyhow::anyhow!("No successful streaming throughput tests for model {}", model))
+        collector
     }
 
-    // Perform a small number of warm-up requests to prime the model and connection pool.
-    async fn warm_up_model(&self, model: &str, streaming: bool) {
-        const WARMUPS: usize = 2;
-        for _ in 0..WARMUPS {
-            let req = ChatCompletionRequest::benchmark_latency_request(model, streaming);
-            if streaming {
-                if let Err(e) = self.client.create_streaming_chat_completion(&req).await {
-                    error!("Warm-up streaming request failed for {}: {}", model, e);
+    pub async fn run_usage_audit(&self, models: Vec<String>, tolerance_pct: f64) -> Result<()> {
+        let models_to_test = self.select_models(&models, None)?;
+
+        info!("Auditing usage accounting on {} models with {} prompts", models_to_test.len(), AUDIT_PROMPTS.len());
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Auditing usage for model: {}", model);
+            let samples = self.collect_usage_audit_samples(&model).await;
+            if samples.is_empty() {
+                error!("Failed to audit usage for {}: no successful requests", model);
+                continue;
+            }
+            let stats = audit::summarize(&model, &samples, tolerance_pct);
+            self.record_stats(&model, "audit_usage", &stats.history_metrics());
+            all_results.push((stats, samples));
+        }
+
+        self.print_usage_audit_results(all_results, tolerance_pct);
+        Ok(())
+    }
+
+    async fn collect_usage_audit_samples(&self, model: &str) -> Vec<UsageAuditSample> {
+        let tokenizers = self.clients.tokenizers();
+        let mut samples = Vec::new();
+
+        for (prompt_name, prompt) in AUDIT_PROMPTS {
+            let mut request = ChatCompletionRequest::simple_text_request(model, prompt, false);
+            request.max_completion_tokens = Some(AUDIT_MAX_COMPLETION_TOKENS);
+            let (local_prompt_tokens, local_source) = tokenizers.count_prompt(model, &request.messages);
+
+            match self.clients.checkout().create_chat_completion(&request).await {
+                Ok((response, _)) => {
+                    let text: String = response
+                        .choices
+                        .iter()
+                        .filter_map(|c| c.message.as_ref())
+                        .map(|m| m.text())
+                        .collect();
+                    samples.push(UsageAuditSample {
+                        prompt_name,
+                        mode: AuditMode::NonStreaming,
+                        local_prompt_tokens,
+                        local_completion_tokens: tokenizers.count(model, &text).0,
+                        local_source,
+                        usage: response.usage,
+                    });
                 }
-            } else {
-                if let Err(e) = self.client.create_chat_completion(&req).await.map(|_| ()) {
-                    error!("Warm-up request failed for {}: {}", model, e);
+                Err(e) => error!("Audit request failed for {} ({}): {}", model, prompt_name, e),
+            }
+
+            match self.clients.checkout().create_streaming_chat_completion(&request).await {
+                Ok(metric) => {
+                    samples.push(UsageAuditSample {
+                        prompt_name,
+                        mode: AuditMode::Streaming,
+                        local_prompt_tokens,
+                        local_completion_tokens: tokenizers.count(model, &metric.output_text).0,
+                        local_source,
+                        usage: metric.usage,
+                    });
                 }
+                Err(e) => error!("Audit streaming request failed for {} ({}): {}", model, prompt_name, e),
             }
         }
+
+        samples
     }
 
-    pub async fn run_comprehensive_benchmark(
+    pub async fn run_prefill_sweep(&self, config: PrefillSweepConfig) -> Result<()> {
+        // Long prompts are expensive, so default to a subset like throughput does
+        let models_to_test = self.select_models(&config.model, Some(5))?;
+
+        let mut lengths = config.lengths.clone();
+        lengths.sort_unstable();
+        lengths.dedup();
+
+        info!("Running prefill sweep over {} input lengths on {} models", lengths.len(), models_to_test.len());
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            let context_window = config
+                .context_windows
+                .get(&model)
+                .or_else(|| self.context_windows.get(&model))
+                .copied();
+            if context_window.is_none() {
+                info!("Context window for {} is unknown; sending every input length", model);
+            }
+
+            info!("Testing prefill for model: {}", model);
+            self.warm_up_model(&model, true).await;
+
+            match self.run_prefill_sweep_test(&model, &lengths, context_window, config.requests, config.concurrency).await {
+                Ok(stats) => {
+                    self.record_stats(&model, "prefill_sweep", &stats.history_metrics());
+                    all_results.push(stats);
+                }
+                Err(e) => error!("Failed to run prefill sweep for {}: {}", model, e),
+            }
+        }
+
+        self.print_prefill_sweep_results(all_results);
+        Ok(())
+    }
+
+    async fn run_prefill_sweep_test(
         &self,
-        latency_requests: usize,
+        model: &str,
+        lengths: &[u32],
+        context_window: Option<u32>,
+        requests: usize,
         concurrency: usize,
-    ) -> Result<()> {
-        info!("🚀 Starting comprehensive benchmark suite");
-        println!();
-        
-        // Run latency benchmarks (regular)
-        info!("📊 Running regular latency benchmarks...");
-        let latency_config = BenchmarkConfig::latency(latency_requests, concurrency, vec![], false);
-        self.run_latency_benchmark(latency_config).await?;
-        
-        println!("\n{}\n", "=".repeat(80));
-        
-        // Run streaming latency benchmarks
-        info!("📡 Running streaming latency benchmarks...");
-        let streaming_config = BenchmarkConfig::latency(latency_requests, concurrency, vec![], true);
-        self.run_latency_benchmark(streaming_config).await?;
-        
-        println!("\n{}\n", "=".repeat(80));
-        
-        // Run throughput benchmarks
-        info!("⚡ Running throughput benchmarks...");
-        let throughput_config = BenchmarkConfig::throughput(concurrency, vec![]);
-        self.run_throughput_benchmark(throughput_config).await?;
+    ) -> Result<PrefillSweepStats> {
+        let tokenizers = self.clients.tokenizers();
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
+        let mut samples = Vec::new();
+        let mut failures = Vec::new();
+        let mut skipped_lengths = Vec::new();
 
-        info!("✅ Comprehensive benchmark suite completed!");
+        for &target in lengths {
+            // Leave room for the completion and a little tokenizer disagreement
+            let max_input = context_window.map(|w| w.saturating_sub(64));
+            if max_input.is_some_and(|max| target > max) {
+                skipped_lengths.push(target);
+                continue;
+            }
+
+            info!("Prefill sweep for {}: {} input tokens", model, target);
+            let semaphore = Arc::new(Semaphore::new(concurrency));
+            let mut tasks = Vec::new();
+
+            for _ in 0..requests {
+                let clients = Arc::clone(&self.clients);
+                let semaphore = Arc::clone(&semaphore);
+                let prompt = sweep::synthetic_prompt(tokenizers, model, target);
+                let request = ChatCompletionRequest::benchmark_prefill_request(model, &prompt);
+
+                let task = tokio::spawn(async move {
+                    let _permit = semaphore.acquire().await.unwrap();
+                    let client = clients.checkout();
+                    let result = client.create_streaming_chat_completion(&request).await;
+                    (request, result)
+                });
+
+                tasks.push(task);
+            }
+
+            for result in join_all(tasks).await {
+                match result {
+                    Ok((request, Ok(metric))) => {
+                        collector.add_goodput_sample(GoodputSample::streaming(&metric));
+                        let input_tokens = metric
+                            .usage
+                            .as_ref()
+                            .map(|u| u.prompt_tokens)
+                            .unwrap_or_else(|| tokenizers.count_prompt(model, &request.messages).0);
+                        samples.push(PrefillSample {
+                            target_tokens: target,
+                            input_tokens,
+                            time_to_first_token: metric.time_to_first_chunk.unwrap_or_default(),
+                        });
+                    }
+                    Ok((_, Err(e))) => {
+                        error!("Prefill request failed for model {} at {} tokens: {}", model, target, e);
+                        collector.add_request_error(&e);
+                        failures.push(target);
+                    }
+                    Err(e) => {
+                        error!("Task error for model {}: {}", model, e);
+                        collector.add_error(format!("Task error: {}", e));
+                        failures.push(target);
+                    }
+                }
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("No successful prefill requests for model {}", model));
+        }
+
+        Ok(sweep::summarize_prefill(model, context_window, lengths, skipped_lengths, &samples, &failures, collector.goodput()))
+    }
+
+    pub async fn run_decode_sweep(&self, config: DecodeSweepConfig) -> Result<()> {
+        // Long generations are expensive, so default to a subset like throughput does
+        let models_to_test = self.select_models(&config.model, Some(5))?;
+
+        let mut lengths = config.output_lengths.clone();
+        lengths.sort_unstable();
+        lengths.dedup();
+
+        info!("Running decode sweep over {} output lengths on {} models", lengths.len(), models_to_test.len());
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing decode for model: {}", model);
+            self.warm_up_model(&model, true).await;
+
+            match self.run_decode_sweep_test(&model, &lengths, config.requests, config.concurrency).await {
+                Ok(stats) => {
+                    self.record_stats(&model, "decode_sweep", &stats.history_metrics());
+                    all_results.push(stats);
+                }
+                Err(e) => error!("Failed to run decode sweep for {}: {}", model, e),
+            }
+        }
+
+        self.print_decode_sweep_results(all_results);
         Ok(())
     }
 
-    fn print_latency_results(&self, results: HashMap<String, Box<dyn std::fmt::Debug>>, streaming: bool) {
-        let benchmark_type = if streaming { "Streaming Latency" } else { "Regular Latency" };
-        
-        println!("\n{} Benchmark Results", benchmark_type);
-        println!("{}", "=".repeat(60));
+    async fn run_decode_sweep_test(
+        &self,
+        model: &str,
+        lengths: &[u32],
+        requests: usize,
+        concurrency: usize,
+    ) -> Result<DecodeSweepStats> {
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
+        let mut samples = Vec::new();
+        let mut failures = Vec::new();
 
-        for (model, stats) in results {
-            println!("\n🤖 Model: {}", model);
-            println!("─────────────────────────────");
-            println!("{:#?}", stats);
+        for &max_tokens in lengths {
+            info!("Decode sweep for {}: max_completion_tokens={}", model, max_tokens);
+            let semaphore = Arc::new(Semaphore::new(concurrency));
+            let mut tasks = Vec::new();
+
+            for _ in 0..requests {
+                let clients = Arc::clone(&self.clients);
+                let semaphore = Arc::clone(&semaphore);
+                let model = model.to_string();
+
+                let task = tokio::spawn(async move {
+                    let _permit = semaphore.acquire().await.unwrap();
+                    let client = clients.checkout();
+                    let request = ChatCompletionRequest::benchmark_decode_request(&model, max_tokens);
+                    client.create_streaming_chat_completion(&request).await
+                });
+
+                tasks.push(task);
+            }
+
+            for result in join_all(tasks).await {
+                match result {
+                    Ok(Ok(metric)) => {
+                        collector.add_goodput_sample(GoodputSample::streaming(&metric));
+                        samples.push(DecodeSample {
+                            max_tokens,
+                            output_tokens: metric.total_tokens,
+                            time_to_first_token: metric.time_to_first_chunk.unwrap_or_default(),
+                            total_duration: metric.total_duration,
+                            finish_reason: metric.finish_reason,
+                        });
+                    }
+                    Ok(Err(e)) => {
+                        error!("Decode request failed for model {} at {} tokens: {}", model, max_tokens, e);
+                        collector.add_request_error(&e);
+                        failures.push(max_tokens);
+                    }
+                    Err(e) => {
+                        error!("Task error for model {}: {}", model, e);
+                        collector.add_error(format!("Task error: {}", e));
+                        failures.push(max_tokens);
+                    }
+                }
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("No successful decode requests for model {}", model));
         }
+
+        Ok(sweep::summarize_decode(model, lengths, &samples, &failures, collector.goodput()))
     }
 
-    fn print_throughput_results(&self, results: HashMap<String, ThroughputStats>) {
-        let test_type = "Streaming Throughput";
-        println!("\n{} Benchmark Results", test_type);
-        println!("{}", "=".repeat(60));
+    pub async fn run_conversation_benchmark(&self, config: ConversationConfig) -> Result<()> {
+        let models_to_test = self.select_models(&config.model, Some(5))?;
 
-        for (model, stats) in results {
-            println!("\n🤖 Model: {}", model);
-            println!("─────────────────────────────");
-            println!("Test Type: {}", test_type);
-            println!("Concurrent Requests: {}", stats.total_requests);
-            println!("Successful Requests: {}", stats.successful_requests);
-            println!("Failed Requests: {}", stats.failed_requests);
-            println!("Success Rate: {:.1}%", stats.success_rate);
-            println!("Average Request Duration: {:?}", stats.test_duration);
-            println!("Average Tokens per Second (pure generation): {:.2}", stats.mean_tokens_per_second);
+        info!(
+            "Running {} {}-turn conversations per model on {} models",
+            config.conversations, config.turns, models_to_test.len()
+        );
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing conversations for model: {}", model);
+            self.warm_up_model(&model, config.streaming).await;
+
+            let stats = self.run_conversation_test(&model, &config).await;
+            if stats.depths.iter().all(|d| d.samples == 0) {
+                error!("Failed to benchmark conversations for {}: no successful turns", model);
+                continue;
+            }
+            self.record_stats(&model, "conversation", &stats.history_metrics());
+            all_results.push(stats);
+        }
+
+        self.print_conversation_results(all_results, config.streaming);
+        Ok(())
+    }
+
+    async fn run_conversation_test(&self, model: &str, config: &ConversationConfig) -> ConversationStats {
+        let semaphore = Arc::new(Semaphore::new(config.concurrency));
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
+        let mut tasks = Vec::new();
+
+        for _ in 0..config.conversations {
+            let clients = Arc::clone(&self.clients);
+            let semaphore = Arc::clone(&semaphore);
+            let model = model.to_string();
+            let turns = config.turns;
+            let streaming = config.streaming;
+
+            let task = tokio::spawn(async move {
+                let _permit = semaphore.acquire().await.unwrap();
+                Self::run_single_conversation(&clients, &model, turns, streaming).await
+            });
+
+            tasks.push(task);
+        }
+
+        let mut conversations = Vec::new();
+        let mut failed_turns = Vec::new();
+
+        for result in join_all(tasks).await {
+            match result {
+                Ok((samples, failed_turn)) => {
+                    // Every turn is a request of its own as far as the SLO is concerned
+                    for sample in &samples {
+                        collector.add_goodput_sample(sample.goodput_sample(config.streaming));
+                    }
+                    if let Some(turn) = failed_turn {
+                        collector.add_error(format!("Turn {} failed", turn));
+                    }
+                    conversations.push(samples);
+                    failed_turns.extend(failed_turn);
+                }
+                Err(e) => {
+                    error!("Task error for model {}: {}", model, e);
+                    collector.add_error(format!("Task error: {}", e));
+                }
+            }
+        }
+
+        conversation::summarize(model, config.turns, &conversations, &failed_turns, collector.goodput())
+    }
+
+    // Plays one conversation to completion, feeding each actual reply back as history.
+    // Stops at the first failed turn, which is returned alongside the completed samples.
+    async fn run_single_conversation(
+        clients: &ClientPool,
+        model: &str,
+        turns: usize,
+        streaming: bool,
+    ) -> (Vec<ConversationTurnSample>, Option<usize>) {
+        let tokenizers = clients.tokenizers();
+        let mut conversation = Conversation::new(model);
+        let mut samples = Vec::new();
+
+        for _ in 0..turns {
+            let request = conversation.next_request(streaming);
+            let turn = conversation.turn();
+            let client = clients.checkout();
+
+            let result = if streaming {
+                client.create_streaming_chat_completion(&request).await.map(|metric| {
+                    let prompt_tokens = metric
+                        .usage
+                        .as_ref()
+                        .map(|u| u.prompt_tokens)
+                        .unwrap_or_else(|| tokenizers.count_prompt(model, &request.messages).0);
+                    let sample = ConversationTurnSample {
+                        turn,
+                        prompt_tokens,
+                        completion_tokens: metric.total_tokens,
+                        time_to_first_token: metric.time_to_first_chunk.unwrap_or_default(),
+                        total_duration: metric.total_duration,
+                    };
+                    (sample, metric.output_text)
+                })
+            } else {
+                client.create_chat_completion(&request).await.map(|(response, metric)| {
+                    let reply: String = response
+                        .choices
+                        .first()
+                        .and_then(|c| c.message.as_ref())
+                        .map(|m| m.text().to_string())
+                        .unwrap_or_default();
+                    let prompt_tokens = response
+                        .usage
+                        .as_ref()
+                        .map(|u| u.prompt_tokens)
+                        .unwrap_or_else(|| tokenizers.count_prompt(model, &request.messages).0);
+                    let completion_tokens = response
+                        .usage
+                        .as_ref()
+                        .and_then(|u| u.completion_tokens)
+                        .unwrap_or_else(|| tokenizers.count(model, &reply).0);
+                    let sample = ConversationTurnSample {
+                        turn,
+                        prompt_tokens,
+                        completion_tokens,
+                        // Without streaming, the first token arrives with the headers
+                        time_to_first_token: metric.time_to_first_byte,
+                        total_duration: metric.total_duration,
+                    };
+                    (sample, reply)
+                })
+            };
+
+            match result {
+                Ok((sample, reply)) => {
+                    samples.push(sample);
+                    conversation.record_reply(reply);
+                }
+                Err(e) => {
+                    error!("Conversation turn {} failed for model {}: {}", turn, model, e);
+                    return (samples, Some(turn));
+                }
+            }
+        }
+
+        (samples, None)
+    }
+
+    pub async fn run_cache_benchmark(&self, config: CacheConfig) -> Result<()> {
+        let models_to_test = self.select_models(&config.model, Some(5))?;
+
+        info!(
+            "Running prompt caching benchmark ({}-token prefix, {} rounds) on {} models",
+            config.prefix_tokens, config.rounds, models_to_test.len()
+        );
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing prompt caching for model: {}", model);
+            self.warm_up_model(&model, true).await;
+
+            match self.run_cache_test(&model, &config).await {
+                Ok(stats) => {
+                    self.record_stats(&model, "cache", &stats.history_metrics());
+                    all_results.push(stats);
+                }
+                Err(e) => error!("Failed to benchmark prompt caching for {}: {}", model, e),
+            }
+        }
+
+        self.print_cache_results(all_results, config.prefix_tokens);
+        Ok(())
+    }
+
+    // Requests run one at a time so cold and warm requests can't overlap.
+    async fn run_cache_test(&self, model: &str, config: &CacheConfig) -> Result<CacheStats> {
+        let tokenizers = self.clients.tokenizers();
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
+        let mut samples = Vec::new();
+
+        for round in 0..config.rounds {
+            // A fresh nonce-led prefix per round guarantees the first request is a cache miss
+            let prefix = sweep::synthetic_text(tokenizers, model, config.prefix_tokens as usize);
+
+            for index in 0..=config.warm_requests {
+                let warm = index > 0;
+                let request = cache::cache_request(model, &prefix, index);
+
+                match self.clients.checkout().create_streaming_chat_completion(&request).await {
+                    Ok(metric) => {
+                        collector.add_goodput_sample(GoodputSample::streaming(&metric));
+                        samples.push(CacheSample {
+                            warm,
+                            time_to_first_token: metric.time_to_first_chunk.unwrap_or_default(),
+                            prompt_tokens: metric.usage.as_ref().map(|u| u.prompt_tokens),
+                            cached_tokens: metric.usage.as_ref().and_then(|u| u.cached_tokens()),
+                        });
+                    }
+                    Err(e) => {
+                        error!(
+                            "{} request failed for model {} (round {}): {}",
+                            if warm { "Warm" } else { "Cold" }, model, round + 1, e
+                        );
+                        collector.add_request_error(&e);
+                    }
+                }
+
+                if !warm {
+                    tokio::time::sleep(config.settle).await;
+                }
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("No successful caching requests for model {}", model));
+        }
+
+        Ok(cache::summarize(model, &samples, collector.goodput()))
+    }
+
+    pub async fn run_tool_benchmark(&self, config: ToolConfig) -> Result<()> {
+        let models_to_test = self.select_models(&config.model, Some(5))?;
+
+        info!(
+            "Running tool-calling benchmark ({} requests, tool_choice={}) on {} models",
+            config.requests, config.tool_choice, models_to_test.len()
+        );
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing tool calling for model: {}", model);
+            self.warm_up_model(&model, true).await;
+
+            match self.run_tool_test(&model, &config).await {
+                Ok(stats) => {
+                    self.record_stats(&model, "tools", &stats.history_metrics());
+                    all_results.push(stats);
+                }
+                Err(e) => error!("Failed to benchmark tool calling for {}: {}", model, e),
+            }
+        }
+
+        self.print_tool_results(all_results);
+        Ok(())
+    }
+
+    async fn run_tool_test(&self, model: &str, config: &ToolConfig) -> Result<ToolCallStats> {
+        let semaphore = Arc::new(Semaphore::new(config.concurrency));
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
+        let mut tasks = Vec::new();
+
+        for _ in 0..config.requests {
+            let clients = Arc::clone(&self.clients);
+            let semaphore = Arc::clone(&semaphore);
+            let model = model.to_string();
+            let tool_choice = config.tool_choice.clone();
+
+            let task = tokio::spawn(async move {
+                let _permit = semaphore.acquire().await.unwrap();
+                Self::run_single_tool_round_trip(&clients, &model, &tool_choice).await
+            });
+
+            tasks.push(task);
+        }
+
+        let mut samples = Vec::new();
+        let mut failed_requests = 0;
+
+        for result in join_all(tasks).await {
+            match result {
+                Ok(Ok((sample, goodput))) => {
+                    collector.add_goodput_sample(goodput);
+                    samples.push(sample);
+                }
+                Ok(Err(e)) => {
+                    error!("Tool request failed for model {}: {}", model, e);
+                    collector.add_request_error(&e);
+                    failed_requests += 1;
+                }
+                Err(e) => {
+                    error!("Task error for model {}: {}", model, e);
+                    collector.add_error(format!("Task error: {}", e));
+                    failed_requests += 1;
+                }
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("No successful tool requests for model {}", model));
+        }
+
+        Ok(tools::summarize(model, &samples, failed_requests, collector.goodput()))
+    }
+
+    // Streams the tool-calling request, validates the emitted arguments, then feeds a
+    // canned tool result back and times the model's answer. A failed second leg is
+    // logged and leaves the round-trip fields empty rather than discarding the sample.
+    // Goodput judges the first leg, the request the tool call came from.
+    async fn run_single_tool_round_trip(clients: &ClientPool, model: &str, tool_choice: &str) -> Result<(ToolCallSample, GoodputSample)> {
+        let request = tools::tool_request(model, tool_choice);
+        let metric = clients.checkout().create_streaming_chat_completion(&request).await?;
+
+        let tool = tools::weather_tool();
+        let called_tool = !metric.tool_calls.is_empty();
+        let mut sample = ToolCallSample {
+            called_tool,
+            valid_arguments: called_tool && metric.tool_calls.iter().all(|call| tools::arguments_valid(call, &tool)),
+            time_to_first_tool_call: metric.time_to_first_tool_call,
+            time_to_arguments_complete: metric.time_to_tool_call_complete,
+            first_leg_duration: metric.total_duration,
+            followup_ttft: None,
+            followup_duration: None,
+        };
+
+        if called_tool {
+            let followup = tools::tool_result_request(&request, &metric.tool_calls);
+            match clients.checkout().create_streaming_chat_completion(&followup).await {
+                Ok(result) => {
+                    sample.followup_ttft = result.time_to_first_chunk;
+                    sample.followup_duration = Some(result.total_duration);
+                }
+                Err(e) => error!("Tool result request failed for model {}: {}", model, e),
+            }
+        }
+
+        Ok((sample, GoodputSample::streaming(&metric)))
+    }
+
+    pub async fn run_structured_benchmark(&self, config: StructuredConfig) -> Result<()> {
+        let models_to_test = self.select_models(&config.model, Some(5))?;
+
+        info!(
+            "Running structured output benchmark ({} requests, {}) on {} models",
+            config.requests,
+            if config.json_schema { "json_schema" } else { "json_object" },
+            models_to_test.len()
+        );
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing structured output for model: {}", model);
+            self.warm_up_model(&model, true).await;
+
+            match self.run_structured_test(&model, &config).await {
+                Ok(stats) => {
+                    self.record_stats(&model, "structured", &stats.history_metrics());
+                    all_results.push(stats);
+                }
+                Err(e) => error!("Failed to benchmark structured output for {}: {}", model, e),
+            }
+        }
+
+        self.print_structured_results(all_results, config.json_schema);
+        Ok(())
+    }
+
+    async fn run_structured_test(&self, model: &str, config: &StructuredConfig) -> Result<StructuredStats> {
+        let semaphore = Arc::new(Semaphore::new(config.concurrency));
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
+        let mut tasks = Vec::new();
+
+        for index in 0..config.requests {
+            let clients = Arc::clone(&self.clients);
+            let semaphore = Arc::clone(&semaphore);
+            let model = model.to_string();
+            let json_schema = config.json_schema;
+
+            let task = tokio::spawn(async move {
+                let _permit = semaphore.acquire().await.unwrap();
+                let client = clients.checkout();
+                let request = structured::structured_request(&model, json_schema, index);
+                client.create_streaming_chat_completion(&request).await
+            });
+
+            tasks.push(task);
+        }
+
+        let mut samples = Vec::new();
+        let mut failed_requests = 0;
+
+        for result in join_all(tasks).await {
+            match result {
+                Ok(Ok(metric)) => {
+                    collector.add_goodput_sample(GoodputSample::streaming(&metric));
+                    samples.push(StructuredSample {
+                        time_to_first_token: metric.time_to_first_chunk.unwrap_or_default(),
+                        total_duration: metric.total_duration,
+                        output_tokens: metric.total_tokens,
+                        conformance: structured::check(&metric.output_text),
+                    });
+                }
+                Ok(Err(e)) => {
+                    error!("Structured output request failed for model {}: {}", model, e);
+                    collector.add_request_error(&e);
+                    failed_requests += 1;
+                }
+                Err(e) => {
+                    error!("Task error for model {}: {}", model, e);
+                    collector.add_error(format!("Task error: {}", e));
+                    failed_requests += 1;
+                }
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("No successful structured output requests for model {}", model));
+        }
+
+        Ok(structured::summarize(model, &samples, failed_requests, collector.goodput()))
+    }
+
+    pub async fn run_vision_benchmark(&self, config: VisionConfig) -> Result<()> {
+        // Only the caller knows which models accept images
+        if config.model.is_empty() {
+            return Err(anyhow::anyhow!("Pass the vision-capable models to test with --model"));
+        }
+        let models_to_test = self.select_models(&config.model, None)?;
+
+        let sources: Vec<ImageSource> = if config.images.is_empty() {
+            config.resolutions.iter().map(|&side| ImageSource::Synthetic(side)).collect()
+        } else {
+            config
+                .images
+                .iter()
+                .map(|path| vision::load_image(path).map(ImageSource::File))
+                .collect::<Result<_>>()?
+        };
+        let cases: Vec<VisionCase> = sources
+            .iter()
+            .flat_map(|source| {
+                config.image_counts.iter().map(|&image_count| VisionCase {
+                    source: source.clone(),
+                    image_count,
+                })
+            })
+            .collect();
+
+        info!(
+            "Running vision benchmark over {} image cases on {} models",
+            cases.len(),
+            models_to_test.len()
+        );
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing vision input for model: {}", model);
+            self.warm_up_model(&model, true).await;
+
+            match self.run_vision_test(&model, &cases, config.requests, config.concurrency).await {
+                Ok(stats) => {
+                    self.record_stats(&model, "vision", &stats.history_metrics());
+                    all_results.push(stats);
+                }
+                Err(e) => error!("Failed to benchmark vision input for {}: {}", model, e),
+            }
+        }
+
+        self.print_vision_results(all_results);
+        Ok(())
+    }
+
+    async fn run_vision_test(
+        &self,
+        model: &str,
+        cases: &[VisionCase],
+        requests: usize,
+        concurrency: usize,
+    ) -> Result<VisionStats> {
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
+        let mut samples = Vec::new();
+        let mut failures = Vec::new();
+
+        for (index, case) in cases.iter().enumerate() {
+            info!("Vision test for {}: {} x{}", model, case.source.label(), case.image_count);
+            let semaphore = Arc::new(Semaphore::new(concurrency));
+            let mut tasks = Vec::new();
+
+            for _ in 0..requests {
+                let clients = Arc::clone(&self.clients);
+                let semaphore = Arc::clone(&semaphore);
+                let model = model.to_string();
+                let case = case.clone();
+
+                let task = tokio::spawn(async move {
+                    // Encode images before taking a permit so it never counts towards TTFT
+                    let images = tokio::task::spawn_blocking(move || case.source.images(case.image_count)).await??;
+                    let _permit = semaphore.acquire().await.unwrap();
+                    let client = clients.checkout();
+                    let request = vision::vision_request(&model, &images);
+                    let metric = client.create_streaming_chat_completion(&request).await?;
+                    let sample = VisionSample {
+                        case: index,
+                        megapixels: images.iter().map(|image| image.megapixels()).sum(),
+                        payload_bytes: images.iter().map(|image| image.data_url.len()).sum(),
+                        time_to_first_token: metric.time_to_first_chunk.unwrap_or_default(),
+                        prompt_tokens: metric.usage.as_ref().map(|u| u.prompt_tokens),
+                    };
+                    Ok::<_, anyhow::Error>((sample, GoodputSample::streaming(&metric)))
+                });
+
+                tasks.push(task);
+            }
+
+            for result in join_all(tasks).await {
+                match result {
+                    Ok(Ok((sample, goodput))) => {
+                        collector.add_goodput_sample(goodput);
+                        samples.push(sample);
+                    }
+                    Ok(Err(e)) => {
+                        error!("Vision request failed for model {} ({}): {}", model, case.source.label(), e);
+                        collector.add_request_error(&e);
+                        failures.push(index);
+                    }
+                    Err(e) => {
+                        error!("Task error for model {}: {}", model, e);
+                        collector.add_error(format!("Task error: {}", e));
+                        failures.push(index);
+                    }
+                }
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("No successful vision requests for model {}", model));
+        }
+
+        Ok(vision::summarize(model, cases, &samples, &failures, collector.goodput()))
+    }
+
+    pub async fn run_embeddings_benchmark(&self, config: EmbeddingConfig) -> Result<()> {
+        // The models endpoint may only list chat models, so requested embedding models are
+        // used even when unlisted.
+        let models_to_test: Vec<String> = if config.model.is_empty() {
+            self.supported_models.iter().filter(|m| m.contains("embed")).cloned().collect()
+        } else {
+            for model in config.model.iter().filter(|m| !self.supported_models.contains(m)) {
+                warn!("Model '{}' is not listed by the models endpoint; trying it anyway", model);
+            }
+            config.model.clone()
+        };
+        if models_to_test.is_empty() {
+            return Err(anyhow::anyhow!("No embedding models found; pass them with --model"));
+        }
+
+        info!(
+            "Running embeddings benchmark over {} batch sizes and {} input lengths on {} models",
+            config.batch_sizes.len(), config.input_tokens.len(), models_to_test.len()
+        );
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing embeddings for model: {}", model);
+
+            match self.run_embeddings_test(&model, &config).await {
+                Ok(stats) => {
+                    self.record_stats(&model, "embeddings", &stats.history_metrics());
+                    all_results.push(stats);
+                }
+                Err(e) => error!("Failed to benchmark embeddings for {}: {}", model, e),
+            }
+        }
+
+        self.print_embeddings_results(all_results);
+        Ok(())
+    }
+
+    async fn run_embeddings_test(&self, model: &str, config: &EmbeddingConfig) -> Result<EmbeddingStats> {
+        let tokenizers = self.clients.tokenizers();
+
+        // Warm up the connection pool with a single short input
+        let warmup = EmbeddingRequest::new(model, vec!["warm-up".to_string()]);
+        if let Err(e) = self.clients.checkout().create_embeddings(&warmup).await {
+            error!("Warm-up embeddings request failed for {}: {}", model, e);
+        }
+
+        let mut collector = MetricsCollector::new().with_goodput_slo(self.goodput_slo);
+        let mut points = Vec::new();
+        let mut samples = Vec::new();
+        let mut failures = Vec::new();
+
+        for &input_tokens in &config.input_tokens {
+            for &batch_size in &config.batch_sizes {
+                info!("Embeddings for {}: batch {} x {} tokens", model, batch_size, input_tokens);
+                // Unique nonce-led inputs so nothing is served from a cache, built up front so
+                // tokenization stays out of the timed window
+                let batches: Vec<(Vec<String>, u32)> = (0..config.requests)
+                    .map(|_| {
+                        let input: Vec<String> = (0..batch_size)
+                            .map(|_| sweep::synthetic_text(tokenizers, model, input_tokens as usize))
+                            .collect();
+                        let local_tokens = input.iter().map(|text| tokenizers.count(model, text).0).sum();
+                        (input, local_tokens)
+                    })
+                    .collect();
+
+                let started = Instant::now();
+                let semaphore = Arc::new(Semaphore::new(config.concurrency));
+                let mut tasks = Vec::new();
+
+                for (input, local_tokens) in batches {
+                    let clients = Arc::clone(&self.clients);
+                    let semaphore = Arc::clone(&semaphore);
+                    let model = model.to_string();
+
+                    let task = tokio::spawn(async move {
+                        let _permit = semaphore.acquire().await.unwrap();
+                        let client = clients.checkout();
+                        let request = EmbeddingRequest::new(&model, input);
+                        let (response, metric) = client.create_embeddings(&request).await?;
+                        Ok::<_, anyhow::Error>(EmbeddingSample {
+                            batch_size,
+                            input_tokens,
+                            latency: metric.total_duration,
+                            tokens: response.usage.as_ref().map_or(local_tokens, |u| u.prompt_tokens),
+                            dimensions: response.data.iter().map(|d| d.embedding.len()).collect(),
+                            vectors: response.data.len(),
+                        })
+                    });
+
+                    tasks.push(task);
+                }
+
+                for result in join_all(tasks).await {
+                    match result {
+                        Ok(Ok(sample)) => {
+                            collector.add_goodput_sample(GoodputSample::request(sample.latency));
+                            samples.push(sample);
+                        }
+                        Ok(Err(e)) => {
+                            error!("Embeddings request failed for model {}: {}", model, e);
+                            collector.add_request_error(&e);
+                            failures.push((batch_size, input_tokens));
+                        }
+                        Err(e) => {
+                            error!("Task error for model {}: {}", model, e);
+                            collector.add_error(format!("Task error: {}", e));
+                            failures.push((batch_size, input_tokens));
+                        }
+                    }
+                }
+                points.push((batch_size, input_tokens, started.elapsed()));
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("No successful embeddings requests for model {}", model));
+        }
+
+        Ok(embeddings::summarize(model, &points, &samples, &failures, collector.goodput()))
+    }
+
+    // Compares TTFT and total time across connection modes to quantify connection setup cost
+    pub async fn run_connection_benchmark(&self, config: ConnectionConfig) -> Result<()> {
+        let models_to_test = self.select_models(&config.model, Some(3))?;
+
+        info!(
+            "Running connection mode comparison ({} requests per mode, concurrency {}) on {} models",
+            config.requests,
+            config.concurrency,
+            models_to_test.len()
+        );
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing connection modes for model: {}", model);
+            // Warm the model itself so only connection handling differs between modes
+            self.warm_up_model(&model, true).await;
+
+            let mut modes = Vec::new();
+            for &mode in &config.modes {
+                let pool = Arc::new(self.clients.with_mode(mode));
+                Self::prewarm_pool(&pool, &model, config.concurrency).await;
+
+                info!("Running {} connection mode for {}", mode, model);
+                let label = mode.to_string();
+                let stats = Self::run_connection_test(&pool, &label, &model, config.requests, config.concurrency, self.goodput_slo)
+                    .await;
+                self.record_stats(&model, &format!("connection_{}", label), &stats.history_metrics());
+                modes.push(stats);
+            }
+
+            all_results.push(ConnectionComparison { model, modes });
+        }
+
+        self.print_connection_results("Connection Mode Comparison", all_results);
+        Ok(())
+    }
+
+    pub async fn run_protocol_benchmark(&self, config: ProtocolConfig) -> Result<()> {
+        let models_to_test = self.select_models(&config.model, Some(3))?;
+
+        info!(
+            "Running HTTP protocol comparison ({} requests per protocol, concurrency {}, {} connections) on {} models",
+            config.requests,
+            config.concurrency,
+            self.clients.mode(),
+            models_to_test.len()
+        );
+
+        let mut all_results = Vec::new();
+
+        for model in models_to_test {
+            info!("Testing HTTP protocols for model: {}", model);
+            self.warm_up_model(&model, true).await;
+
+            let mut versions = Vec::new();
+            for &version in &config.versions {
+                let pool = Arc::new(self.clients.with_http_version(version));
+                Self::prewarm_pool(&pool, &model, config.concurrency).await;
+
+                info!("Running {} for {}", version, model);
+                let label = version.to_string();
+                let stats = Self::run_connection_test(&pool, &label, &model, config.requests, config.concurrency, self.goodput_slo)
+                    .await;
+                self.record_stats(&model, &format!("protocol_{}", label), &stats.history_metrics());
+                versions.push(stats);
+            }
+
+            all_results.push(ConnectionComparison { model, modes: versions });
+        }
+
+        self.print_connection_results("HTTP Protocol Comparison", all_results);
+        Ok(())
+    }
+
+    // For warm pools, opens the connections `concurrency` workers will use so the measured
+    // requests reuse them. Other modes open connections as part of the measurement.
+    async fn prewarm_pool(pool: &Arc<ClientPool>, model: &str, concurrency: usize) {
+        if pool.mode() != ConnectionMode::Warm {
+            return;
+        }
+        let warmups = (0..concurrency).map(|_| {
+            let request = ChatCompletionRequest::benchmark_latency_request(model, true);
+            async move {
+                if let Err(e) = pool.checkout().create_streaming_chat_completion(&request).await {
+                    error!("Connection warm-up failed for {}: {}", model, e);
+                }
+            }
+        });
+        join_all(warmups).await;
+    }
+
+    async fn run_connection_test(
+        pool: &Arc<ClientPool>,
+        label: &str,
+        model: &str,
+        requests: usize,
+        concurrency: usize,
+        goodput_slo: Option<GoodputSlo>,
+    ) -> connection::ConnectionModeStats {
+        let semaphore = Arc::new(Semaphore::new(concurrency));
+        let mut tasks = Vec::new();
+        let started = Instant::now();
+        let mut collector = MetricsCollector::new().with_goodput_slo(goodput_slo);
+
+        for _ in 0..requests {
+            let clients = Arc::clone(pool);
+            let semaphore = Arc::clone(&semaphore);
+            let model = model.to_string();
+
+            let task = tokio::spawn(async move {
+                let _permit = semaphore.acquire().await.unwrap();
+                let client = clients.checkout();
+
+                let request = ChatCompletionRequest::benchmark_latency_request(&model, true);
+                client.create_streaming_chat_completion(&request).await
+            });
+
+            tasks.push(task);
+        }
+
+        let results = join_all(tasks).await;
+        let wall_time = started.elapsed();
+
+        let mut samples = Vec::new();
+        let mut failed_requests = 0;
+
+        for result in results {
+            match result {
+                Ok(Ok(metric)) => {
+                    collector.add_goodput_sample(GoodputSample::streaming(&metric));
+                    samples.push(ConnectionSample {
+                        time_to_first_token: metric.time_to_first_chunk.unwrap_or(metric.total_duration),
+                        total_duration: metric.total_duration,
+                        http_version: metric.http_version,
+                        phases: metric.phases,
+                    });
+                }
+                Ok(Err(e)) => {
+                    error!("Request failed for model {} ({}): {}", model, label, e);
+                    collector.add_request_error(&e);
+                    failed_requests += 1;
+                }
+                Err(e) => {
+                    error!("Task error for model {}: {}", model, e);
+                    collector.add_error(format!("Task error: {}", e));
+                    failed_requests += 1;
+                }
+            }
+        }
+
+        connection::summarize(label, &samples, failed_requests, wall_time, collector.goodput())
+    }
+
+    // Probes each model every `interval` and serves the results on `/metrics` until killed
+    pub async fn run_metrics_server(&self, config: ServeMetricsConfig) -> Result<()> {
+        let models_to_probe = self.select_models(&config.model, Some(3))?;
+        let exporter = Arc::new(Exporter::new()?);
+
+        let listener = tokio::net::TcpListener::bind(config.listen).await?;
+        info!("Serving Prometheus metrics on http://{}/metrics", listener.local_addr()?);
+        tokio::spawn(exporter::serve(listener, Arc::clone(&exporter)));
+
+        info!(
+            "Probing {} models every {:?} ({} streaming requests each, concurrency {})",
+            models_to_probe.len(),
+            config.interval,
+            config.requests,
+            config.concurrency
+        );
+
+        let mut ticker = tokio::time::interval(config.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            for model in &models_to_probe {
+                let collector = self.collect_streaming_metrics(model, config.requests, config.concurrency).await;
+                info!(
+                    "Probed {}: {} succeeded, {} failed",
+                    model,
+                    collector.streaming_metrics().len(),
+                    collector.failures().len()
+                );
+                exporter.record(model, &collector);
+            }
+        }
+    }
+
+    // Probes each model every `interval`, evaluates the SLOs over the trailing window and
+    // posts to the webhook when a model starts or stops breaching them. Runs until killed.
+    pub async fn run_monitor(&self, config: MonitorConfig) -> Result<()> {
+        let models_to_probe = self.select_models(&config.model, Some(3))?;
+        let mut monitors: Vec<ModelMonitor> = models_to_probe.iter().map(|m| ModelMonitor::new(m)).collect();
+        let webhook_client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?;
+
+        info!(
+            "Monitoring {} models every {:?} over a {:?} window{}",
+            monitors.len(),
+            config.interval,
+            config.slo.window,
+            if config.webhook.is_some() { "" } else { " (no webhook, alerts are only logged)" }
+        );
+
+        let mut ticker = tokio::time::interval(config.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            for monitor in &mut monitors {
+                let collector = self
+                    .collect_streaming_metrics(monitor.model(), config.requests, config.concurrency)
+                    .await;
+                monitor.record(&collector);
+                let evaluation = monitor.evaluate(&config.slo);
+                info!(
+                    "{}: {} requests in window, success {:.2}%, p95 TTFC {}{}",
+                    monitor.model(),
+                    evaluation.requests,
+                    evaluation.success_rate,
+                    evaluation.p95_ttfc_ms.map_or_else(|| "-".to_string(), |ms| format!("{:.0}ms", ms)),
+                    if evaluation.breached() { format!(" ❌ {}", evaluation.violations.join("; ")) } else { String::new() }
+                );
+
+                let Some(alert) = monitor.transition(&config.slo, &evaluation) else {
+                    continue;
+                };
+                warn!("SLO {:?} for {}", alert.event, alert.model);
+                if let Some(url) = &config.webhook {
+                    if let Err(e) = monitor::send_webhook(&webhook_client, url, &alert).await {
+                        error!("Failed to send {:?} alert for {}: {}", alert.event, alert.model, e);
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn run_report(&self, config: ReportConfig) -> Result<()> {
+        let models_to_test = self.select_models(&config.model, Some(5))?;
+        info!("Collecting report data for {} models", models_to_test.len());
+
+        let mut reports = Vec::new();
+        for model in models_to_test {
+            info!("Testing model: {}", model);
+            self.warm_up_model(&model, true).await;
+
+            let latency = self.collect_streaming_metrics(&model, config.requests, config.concurrency).await;
+            if let Some(stats) = latency.calculate_streaming_stats(&model) {
+                let mut metrics = stats.history_metrics();
+                metrics.push(("success_rate", latency.success_rate()));
+                self.record_results(&model, "streaming_latency", &metrics, &latency);
+            }
+            let throughput = self.collect_throughput_metrics(&model, config.concurrency).await;
+            if let Some(stats) = throughput.calculate_throughput_stats(&model) {
+                self.record_results(&model, "throughput", &stats.history_metrics(), &throughput);
+            }
+
+            reports.push(ModelReport { model, latency, throughput });
+        }
+
+        let generated_at = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+        let html = report::render("Sudo API Benchmark Report", &generated_at, &reports);
+        if let Some(dir) = config.html.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&config.html, html)?;
+        println!("\n📄 Report written to {}", config.html.display());
+        Ok(())
+    }
+
+    // Serves the coordinator one request at a time: `GET /health`, and `POST /run` with a
+    // `RunRequest`, answered with an `AgentResult` once the scenario has run
+    pub async fn run_agent(&self, config: AgentConfig) -> Result<()> {
+        let listener = TcpListener::bind(config.listen).await?;
+        info!("Agent listening on {}", listener.local_addr()?);
+
+        loop {
+            let (mut stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    error!("Failed to accept coordinator connection: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = self.handle_agent_request(&mut stream, &config.token).await {
+                warn!("Request from {} failed: {}", peer, e);
+            }
+        }
+    }
+
+    async fn handle_agent_request(&self, stream: &mut TcpStream, token: &str) -> Result<()> {
+        let request = tokio::time::timeout(Duration::from_secs(10), server::read_request(stream)).await??;
+        if !distributed::authorized(&request, token) {
+            return server::write_response(stream, "401 Unauthorized", "text/plain", b"missing or wrong token\n").await;
+        }
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/health") => server::write_response(stream, "200 OK", "text/plain", b"ok\n").await,
+            ("POST", "/run") => {
+                let run: RunRequest = match serde_json::from_slice(&request.body) {
+                    Ok(run) => run,
+                    Err(e) => {
+                        let body = format!("invalid run request: {}\n", e);
+                        return server::write_response(stream, "400 Bad Request", "text/plain", body.as_bytes()).await;
+                    }
+                };
+                if let Err(e) = run.scenario.validate() {
+                    let body = format!("invalid scenario: {}\n", e);
+                    return server::write_response(stream, "400 Bad Request", "text/plain", body.as_bytes()).await;
+                }
+                match self.run_scenario(run).await {
+                    Ok(result) => {
+                        server::write_response(stream, "200 OK", "application/json", &serde_json::to_vec(&result)?).await
+                    }
+                    Err(e) => {
+                        error!("Scenario failed: {}", e);
+                        let body = format!("{}\n", e);
+                        server::write_response(stream, "500 Internal Server Error", "text/plain", body.as_bytes()).await
+                    }
+                }
+            }
+            _ => server::write_response(stream, "404 Not Found", "text/plain", b"not found\n").await,
+        }
+    }
+
+    async fn run_scenario(&self, run: RunRequest) -> Result<AgentResult> {
+        let scenario = run.scenario;
+        let model = &self.select_models(std::slice::from_ref(&scenario.model), None)?[0];
+        self.warm_up_model(model, scenario.streaming).await;
+
+        let start_at = UNIX_EPOCH + Duration::from_millis(run.start_at_ms);
+        match start_at.duration_since(SystemTime::now()) {
+            Ok(wait) => tokio::time::sleep(wait).await,
+            Err(e) => warn!("Starting {:?} after the coordinated start time", e.duration()),
+        }
+        let start_lag = SystemTime::now().duration_since(start_at).unwrap_or_default();
+        info!(
+            "Running {} {} requests to {} at concurrency {}",
+            scenario.requests,
+            if scenario.streaming { "streaming" } else { "non-streaming" },
+            model,
+            scenario.concurrency
+        );
+
+        let started = Instant::now();
+        let collector = if scenario.streaming {
+            self.collect_streaming_metrics(model, scenario.requests, scenario.concurrency).await
+        } else {
+            self.collect_latency_metrics(model, scenario.requests, scenario.concurrency).await
+        };
+        let wall_time = started.elapsed();
+
+        let mut failures = BTreeMap::new();
+        for label in collector.failures() {
+            *failures.entry(label.clone()).or_insert(0) += 1;
+        }
+        let mut hdr_log = Vec::new();
+        hdr::write_log(&mut hdr_log, collector.started_at(), &hdr::intervals(model, &collector))?;
+
+        Ok(AgentResult {
+            succeeded: collector.latency_metrics().len() + collector.streaming_metrics().len(),
+            failures,
+            wall_time_ms: wall_time.as_secs_f64() * 1000.0,
+            start_lag_ms: start_lag.as_secs_f64() * 1000.0,
+            hdr_log: String::from_utf8(hdr_log)?,
+        })
+    }
+
+    // Perform a small number of warm-up requests to prime the model and connection pool.
+    async fn warm_up_model(&self, model: &str, streaming: bool) {
+        const WARMUPS: usize = 2;
+        for _ in 0..WARMUPS {
+            let req = ChatCompletionRequest::benchmark_latency_request(model, streaming);
+            if streaming {
+                if let Err(e) = self.clients.checkout().create_streaming_chat_completion(&req).await {
+                    error!("Warm-up streaming request failed for {}: {}", model, e);
+                }
+            } else {
+                if let Err(e) = self.clients.checkout().create_chat_completion(&req).await.map(|_| ()) {
+                    error!("Warm-up request failed for {}: {}", model, e);
+                }
+            }
+        }
+    }
+
+    pub async fn run_comprehensive_benchmark(
+        &self,
+        latency_requests: usize,
+        concurrency: usize,
+    ) -> Result<()> {
+        info!("🚀 Starting comprehensive benchmark suite");
+        println!();
+        
+        // Run latency benchmarks (regular)
+        info!("📊 Running regular latency benchmarks...");
+        let latency_config = BenchmarkConfig::latency(latency_requests, concurrency, vec![], false);
+        self.run_latency_benchmark(latency_config).await?;
+        
+        println!("\n{}\n", "=".repeat(80));
+        
+        // Run streaming latency benchmarks
+        info!("📡 Running streaming latency benchmarks...");
+        let streaming_config = BenchmarkConfig::latency(latency_requests, concurrency, vec![], true);
+        self.run_latency_benchmark(streaming_config).await?;
+        
+        println!("\n{}\n", "=".repeat(80));
+        
+        // Run throughput benchmarks
+        info!("⚡ Running throughput benchmarks...");
+        let throughput_config = BenchmarkConfig::throughput(concurrency, vec![]);
+        self.run_throughput_benchmark(throughput_config).await?;
+
+        info!("✅ Comprehensive benchmark suite completed!");
+        Ok(())
+    }
+
+    fn print_latency_results(&self, results: HashMap<String, Box<dyn std::fmt::Debug>>, streaming: bool) {
+        let benchmark_type = if streaming { "Streaming Latency" } else { "Regular Latency" };
+        
+        println!("\n{} Benchmark Results", benchmark_type);
+        println!("{}", "=".repeat(60));
+
+        for (model, stats) in results {
+            println!("\n🤖 Model: {}", model);
+            println!("─────────────────────────────");
+            println!("{:#?}", stats);
+        }
+    }
+
+    fn print_throughput_results(&self, results: HashMap<String, ThroughputStats>) {
+        let test_type = "Streaming Throughput";
+        println!("\n{} Benchmark Results", test_type);
+        println!("{}", "=".repeat(60));
+
+        for (model, stats) in results {
+            println!("\n🤖 Model: {}", model);
+            println!("─────────────────────────────");
+            println!("Test Type: {}", test_type);
+            println!("Concurrent Requests: {}", stats.total_requests);
+            println!("Successful Requests: {}", stats.successful_requests);
+            println!("Failed Requests: {}", stats.failed_requests);
+            println!("Success Rate: {:.1}%", stats.success_rate);
+            println!("Average Request Duration: {:?}", stats.test_duration);
+            println!("Average Tokens per Second (pure generation): {:.2}", stats.mean_tokens_per_second);
+            println!("Token Counts From: {}", stats.token_sources);
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+            if let Some(wait) = &stats.limiter_wait {
+                println!("Rate Limiter Wait: {}", wait);
+            }
+            if let Some(reasoning) = &stats.reasoning {
+                println!("Reasoning Requests: {}", reasoning.reasoning_requests);
+                println!("Mean Reasoning Tokens: {}", reasoning.mean_reasoning_tokens);
+                println!("Mean Visible Tokens: {}", reasoning.mean_visible_tokens);
+                println!("Mean Thinking Time: {:?}", reasoning.mean_thinking_duration);
+                println!("P95 Thinking Time: {:?}", reasoning.p95_thinking_duration);
+                println!("Mean Visible TTFT: {:?}", reasoning.mean_time_to_first_visible_token);
+                println!("P95 Visible TTFT: {:?}", reasoning.p95_time_to_first_visible_token);
+                println!("Average Visible Tokens per Second: {:.2}", reasoning.mean_visible_tokens_per_second);
+            }
+            if let Some(timeouts) = &stats.timeouts {
+                println!("Timed Out Requests: {}", timeouts.count);
+                for (phase, phase_stats) in &timeouts.phases {
+                    println!(
+                        "  {} timeout: {} (mean fired at {:.2?}, latest {:.2?})",
+                        phase, phase_stats.count, phase_stats.mean_fired_at, phase_stats.max_fired_at
+                    );
+                }
+            }
+        }
+    }
+
+    fn print_usage_audit_results(&self, results: Vec<(UsageAuditStats, Vec<UsageAuditSample>)>, tolerance_pct: f64) {
+        println!("\nUsage Accounting Audit Results");
+        println!("{}", "=".repeat(60));
+        println!("Discrepancy threshold: >{:.1}% and >2 tokens", tolerance_pct);
+
+        for (stats, samples) in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            println!("Local Token Counts From: {}", stats.local_source);
+            for sample in &samples {
+                let Some(usage) = &sample.usage else {
+                    println!(
+                        "  {:<13} {:<14} ⚠️  no usage reported (local prompt {}, completion {})",
+                        sample.prompt_name, sample.mode, sample.local_prompt_tokens, sample.local_completion_tokens
+                    );
+                    continue;
+                };
+                let prompt_diff = sample.prompt_diff().unwrap_or(0);
+                let prompt_flag = if audit::is_discrepancy(prompt_diff, sample.local_prompt_tokens, tolerance_pct) { " ⚠️" } else { "" };
+                let completion = match (usage.completion_tokens, sample.completion_diff()) {
+                    (Some(ct), Some(diff)) => {
+                        let flag = if audit::is_discrepancy(diff, sample.local_completion_tokens, tolerance_pct) { " ⚠️" } else { "" };
+                        format!("server {} vs local {} ({:+}){}", ct, sample.local_completion_tokens, diff, flag)
+                    }
+                    _ => format!("server missing vs local {} ⚠️", sample.local_completion_tokens),
+                };
+                println!(
+                    "  {:<13} {:<14} prompt: server {} vs local {} ({:+}){} | completion: {}",
+                    sample.prompt_name,
+                    sample.mode,
+                    usage.prompt_tokens,
+                    sample.local_prompt_tokens,
+                    prompt_diff,
+                    prompt_flag,
+                    completion
+                );
+            }
+            println!("Samples: {}", stats.samples);
+            println!("Streams Without Usage Chunk: {}", stats.missing_usage_streaming);
+            println!("Non-Streaming Responses Without Usage: {}", stats.missing_usage_non_streaming);
+            println!("Prompt Token Discrepancies: {} (mean diff {:+.1}%)", stats.prompt_discrepancies, stats.mean_prompt_diff_pct);
+            println!("Completion Token Discrepancies: {} (mean diff {:+.1}%)", stats.completion_discrepancies, stats.mean_completion_diff_pct);
+        }
+    }
+
+    fn print_prefill_sweep_results(&self, results: Vec<PrefillSweepStats>) {
+        println!("\nPrefill Sweep Results (TTFT vs input length)");
+        println!("{}", "=".repeat(60));
+
+        for stats in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            match stats.context_window {
+                Some(window) => println!("Context Window: {} tokens", window),
+                None => println!("Context Window: unknown"),
+            }
+            println!("  {:>8}  {:>12}  {:>7}  {:>12}  {:>12}", "Target", "Input Tokens", "OK/Fail", "Mean TTFT", "P50 TTFT");
+            for point in &stats.points {
+                println!(
+                    "  {:>8}  {:>12}  {:>7}  {:>12}  {:>12}",
+                    point.target_tokens,
+                    point.mean_input_tokens,
+                    format!("{}/{}", point.successful_requests, point.failed_requests),
+                    format!("{:.0?}", point.mean_ttft),
+                    format!("{:.0?}", point.p50_ttft),
+                );
+            }
+            if !stats.skipped_lengths.is_empty() {
+                let skipped: Vec<String> = stats.skipped_lengths.iter().map(|l| l.to_string()).collect();
+                println!("Skipped (exceed context window): {}", skipped.join(", "));
+            }
+            match stats.fit {
+                Some(fit) => println!(
+                    "Prefill Cost: {:.2} ms per 1k input tokens (intercept {:.0} ms, R² {:.3})",
+                    fit.slope, fit.intercept, fit.r_squared
+                ),
+                None => println!("Prefill Cost: not enough distinct input lengths to fit"),
+            }
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+        }
+    }
+
+    fn print_decode_sweep_results(&self, results: Vec<DecodeSweepStats>) {
+        println!("\nDecode Sweep Results (generation speed vs output length)");
+        println!("{}", "=".repeat(60));
+
+        for stats in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            println!(
+                "  {:>10}  {:>7}  {:>11}  {:>8}  {:>10}  {:>10}  {:>10}  {:>12}",
+                "Max Tokens", "OK/Fail", "Mean Output", "TPS", "TPOT", "Mean E2E", "P95 E2E", "Stopped Early"
+            );
+            for point in &stats.points {
+                println!(
+                    "  {:>10}  {:>7}  {:>11}  {:>8.2}  {:>10}  {:>10}  {:>10}  {:>12}",
+                    point.max_tokens,
+                    format!("{}/{}", point.successful_requests, point.failed_requests),
+                    point.mean_output_tokens,
+                    point.mean_tokens_per_second,
+                    format!("{:.1?}", point.mean_tpot),
+                    format!("{:.0?}", point.mean_total_duration),
+                    format!("{:.0?}", point.p95_total_duration),
+                    format!("{}/{}", point.stopped_early, point.successful_requests),
+                );
+            }
+            if stats.points.iter().any(|p| p.stopped_early > 0) {
+                println!("⚠️  Some requests finished before reaching max_completion_tokens; their TPS reflects shorter outputs.");
+            }
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+        }
+    }
+
+    fn print_conversation_results(&self, results: Vec<ConversationStats>, streaming: bool) {
+        let ttft_label = if streaming { "TTFT" } else { "TTFB" };
+        println!("\nConversation Benchmark Results ({})", if streaming { "streaming" } else { "non-streaming" });
+        println!("{}", "=".repeat(60));
+
+        for stats in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            println!("Completed Conversations: {}/{}", stats.completed_conversations, stats.conversations);
+            println!(
+                "  {:>4}  {:>7}  {:>10}  {:>10}  {:>10}  {:>13}  {:>17}  {:>17}",
+                "Turn", "OK/Fail", format!("Mean {}", ttft_label), format!("P95 {}", ttft_label),
+                "Mean E2E", "Prompt Tokens", "Completion Tokens", "Cumulative Tokens"
+            );
+            for depth in &stats.depths {
+                println!(
+                    "  {:>4}  {:>7}  {:>10}  {:>10}  {:>10}  {:>13}  {:>17}  {:>17}",
+                    depth.turn,
+                    format!("{}/{}", depth.samples, depth.failures),
+                    format!("{:.0?}", depth.mean_ttft),
+                    format!("{:.0?}", depth.p95_ttft),
+                    format!("{:.0?}", depth.mean_total_duration),
+                    depth.mean_prompt_tokens,
+                    depth.mean_completion_tokens,
+                    depth.mean_cumulative_tokens,
+                );
+            }
+            if let Some(fit) = stats.ttft_fit {
+                println!(
+                    "{} Growth: {:.2} ms per 1k context tokens (intercept {:.0} ms, R² {:.3})",
+                    ttft_label, fit.slope, fit.intercept, fit.r_squared
+                );
+            }
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+        }
+    }
+
+    fn print_cache_results(&self, results: Vec<CacheStats>, prefix_tokens: u32) {
+        let ratio = |r: Option<f64>| r.map_or("not reported".to_string(), |r| format!("{:.1}%", r * 100.0));
+
+        println!("\nPrompt Caching Benchmark Results ({}-token shared prefix)", prefix_tokens);
+        println!("{}", "=".repeat(60));
+
+        for stats in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            println!("Cold Requests: {}", stats.cold_requests);
+            println!("Warm Requests: {}", stats.warm_requests);
+            println!("Mean TTFT (cold): {:?}", stats.mean_cold_ttft);
+            println!("Mean TTFT (warm): {:?}", stats.mean_warm_ttft);
+            println!("P50 TTFT (cold): {:?}", stats.p50_cold_ttft);
+            println!("P50 TTFT (warm): {:?}", stats.p50_warm_ttft);
+            println!("TTFT Reduction: {:.1}%", stats.ttft_reduction_pct);
+            println!("Cached Token Ratio (cold): {}", ratio(stats.cold_cached_ratio));
+            println!("Cached Token Ratio (warm): {}", ratio(stats.warm_cached_ratio));
+            println!("Warm Cache Hit Rate: {}", ratio(stats.warm_hit_rate));
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+        }
+    }
+
+    fn print_tool_results(&self, results: Vec<ToolCallStats>) {
+        println!("\nTool Calling Benchmark Results");
+        println!("{}", "=".repeat(60));
+
+        for stats in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            println!("Requests: {} ({} failed)", stats.requests, stats.failed_requests);
+            println!("Tool Call Rate: {:.1}%", stats.tool_call_rate);
+            println!("Valid Arguments: {:.1}%", stats.valid_arguments_rate);
+            println!("Mean Time to First Tool Call: {:?}", stats.mean_time_to_first_tool_call);
+            println!("P95 Time to First Tool Call: {:?}", stats.p95_time_to_first_tool_call);
+            println!("Mean Time to Complete Arguments: {:?}", stats.mean_time_to_arguments_complete);
+            println!("P95 Time to Complete Arguments: {:?}", stats.p95_time_to_arguments_complete);
+            println!("Mean TTFT After Tool Result: {:?}", stats.mean_followup_ttft);
+            println!("Mean Round Trip: {:?}", stats.mean_round_trip);
+            println!("P95 Round Trip: {:?}", stats.p95_round_trip);
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+        }
+    }
+
+    fn print_structured_results(&self, results: Vec<StructuredStats>, json_schema: bool) {
+        println!(
+            "\nStructured Output Benchmark Results ({})",
+            if json_schema { "json_schema" } else { "json_object" }
+        );
+        println!("{}", "=".repeat(60));
+
+        for stats in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            println!("Requests: {} ({} failed)", stats.requests, stats.failed_requests);
+            println!("Schema Conformance: {:.1}%", stats.conformance_rate);
+            println!("Invalid JSON: {}", stats.invalid_json);
+            println!("Schema Violations: {}", stats.schema_violations);
+            println!("Mean TTFT: {:?}", stats.mean_ttft);
+            println!("P95 TTFT: {:?}", stats.p95_ttft);
+            println!("Mean Total Time: {:?}", stats.mean_total_duration);
+            println!("P95 Total Time: {:?}", stats.p95_total_duration);
+            println!("Mean Tokens/Second: {:.1}", stats.mean_tokens_per_second);
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+            for failure in &stats.example_failures {
+                println!("  ⚠️  {}", failure);
+            }
+        }
+    }
+
+    fn print_vision_results(&self, results: Vec<VisionStats>) {
+        println!("\nVision Benchmark Results (TTFT vs image count and resolution)");
+        println!("{}", "=".repeat(60));
+
+        for stats in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            println!(
+                "  {:<22}  {:>9}  {:>6}  {:>9}  {:>7}  {:>12}  {:>12}  {:>13}",
+                "Image", "Size", "Images", "Payload", "OK/Fail", "Mean TTFT", "P50 TTFT", "Prompt Tokens"
+            );
+            for point in &stats.points {
+                println!(
+                    "  {:<22}  {:>9}  {:>6}  {:>9}  {:>7}  {:>12}  {:>12}  {:>13}",
+                    point.label,
+                    format!("{}x{}", point.width, point.height),
+                    point.image_count,
+                    format!("{} KB", point.payload_bytes / 1024),
+                    format!("{}/{}", point.successful_requests, point.failed_requests),
+                    format!("{:.0?}", point.mean_ttft),
+                    format!("{:.0?}", point.p50_ttft),
+                    point.mean_prompt_tokens.map_or("n/a".to_string(), |t| t.to_string()),
+                );
+            }
+            match stats.fit {
+                Some(fit) => println!(
+                    "Image Cost: {:.2} ms per megapixel (intercept {:.0} ms, R² {:.3})",
+                    fit.slope, fit.intercept, fit.r_squared
+                ),
+                None => println!("Image Cost: not enough distinct image sizes to fit"),
+            }
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+        }
+    }
+
+    fn print_embeddings_results(&self, results: Vec<EmbeddingStats>) {
+        println!("\nEmbeddings Benchmark Results");
+        println!("{}", "=".repeat(60));
+
+        for stats in results {
+            println!("\n🤖 Model: {}", stats.model);
+            println!("─────────────────────────────");
+            println!(
+                "  {:>5}  {:>6}  {:>7}  {:>10}  {:>10}  {:>10}  {:>10}  {:>10}  {:>10}",
+                "Batch", "Tokens", "OK/Fail", "Mean", "P50", "P95", "P99", "Inputs/s", "Tokens/s"
+            );
+            for point in &stats.points {
+                println!(
+                    "  {:>5}  {:>6}  {:>7}  {:>10}  {:>10}  {:>10}  {:>10}  {:>10.1}  {:>10.0}",
+                    point.batch_size,
+                    point.input_tokens,
+                    format!("{}/{}", point.successful_requests, point.failed_requests),
+                    format!("{:.0?}", point.mean_latency),
+                    format!("{:.0?}", point.p50_latency),
+                    format!("{:.0?}", point.p95_latency),
+                    format!("{:.0?}", point.p99_latency),
+                    point.inputs_per_second,
+                    point.tokens_per_second,
+                );
+            }
+            let dimensions: Vec<String> = stats
+                .dimensions
+                .iter()
+                .map(|(dimension, count)| format!("{} ({} vectors)", dimension, count))
+                .collect();
+            if stats.dimensions_consistent() {
+                println!("Dimensions: {} ✅", dimensions.join(", "));
+            } else {
+                println!("Dimensions: {} ⚠️  inconsistent", dimensions.join(", "));
+            }
+            if stats.count_mismatches > 0 {
+                println!("⚠️  {} responses returned a different number of vectors than inputs sent", stats.count_mismatches);
+            }
+            if let Some(goodput) = &stats.goodput {
+                println!("Goodput: {}", goodput);
+            }
+        }
+    }
+
+    fn print_connection_results(&self, title: &str, results: Vec<ConnectionComparison>) {
+        println!("\n{} Results", title);
+        println!("{}", "=".repeat(60));
+
+        for comparison in results {
+            println!("\n🤖 Model: {}", comparison.model);
+            println!("─────────────────────────────");
+            println!(
+                "  {:<10}  {:>7}  {:>10}  {:>10}  {:>10}  {:>10}  {:>10}  {:>8}  {:>10}  {:>9}  {:>10}",
+                "Setting", "OK/Fail", "Mean TTFT", "P50 TTFT", "P95 TTFT", "Mean Total", "P95 Total", "Req/s", "vs First", "New Conns", "Setup"
+            );
+            for stats in &comparison.modes {
+                println!(
+                    "  {:<10}  {:>7}  {:>10}  {:>10}  {:>10}  {:>10}  {:>10}  {:>8.1}  {:>10}  {:>9}  {:>10}",
+                    stats.label,
+                    format!("{}/{}", stats.requests, stats.failed_requests),
+                    format!("{:.0?}", stats.mean_ttft),
+                    format!("{:.0?}", stats.p50_ttft),
+                    format!("{:.0?}", stats.p95_ttft),
+                    format!("{:.0?}", stats.mean_total_duration),
+                    format!("{:.0?}", stats.p95_total_duration),
+                    stats.requests_per_second,
+                    comparison
+                        .ttft_overhead(stats)
+                        .map_or_else(|| "-".to_string(), |ms| format!("{:+.1}ms", ms)),
+                    stats.new_connections.map_or_else(|| "-".to_string(), |n| n.to_string()),
+                    stats
+                        .mean_connection_setup
+                        .map_or_else(|| "-".to_string(), |d| format!("{:.1?}", d)),
+                );
+            }
+            for stats in &comparison.modes {
+                let negotiated: Vec<String> = stats
+                    .http_versions
+                    .iter()
+                    .map(|(version, count)| format!("{} ({})", version, count))
+                    .collect();
+                if !negotiated.is_empty() {
+                    println!("Negotiated protocol ({}): {}", stats.label, negotiated.join(", "));
+                }
+                if let Some(goodput) = &stats.goodput {
+                    println!("Goodput ({}): {}", stats.label, goodput);
+                }
+            }
+            if comparison.modes.iter().all(|m| m.new_connections.is_none()) {
+                println!("(run with --phase-timing to count new connections and time their setup)");
+            }
         }
     }
 }
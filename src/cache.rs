@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use crate::metrics::{mean_and_percentile, ms, GoodputStats};
+use crate::models::ChatCompletionRequest;
+
+// Suffixes appended to the shared prefix; each warm request uses a different one so only
+// the prefix can be served from cache.
+const CACHE_SUFFIXES: &[&str] = &[
+    "Which word appears first in the text above?",
+    "How many distinct words does the text above roughly contain?",
+    "Name one word from the text above that relates to weather.",
+    "Name one word from the text above that relates to travel.",
+    "Which word in the text above is the longest?",
+    "Name one word from the text above that relates to food.",
+    "Name one word from the text above that relates to buildings.",
+    "Name one word from the text above that relates to nature.",
+];
+
+pub fn cache_request(model: &str, prefix: &str, index: usize) -> ChatCompletionRequest {
+    let prompt = format!(
+        "{}\n\nAnswer in one word. {}",
+        prefix,
+        CACHE_SUFFIXES[index % CACHE_SUFFIXES.len()]
+    );
+    ChatCompletionRequest::benchmark_prefill_request(model, &prompt)
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheSample {
+    pub warm: bool,
+    pub time_to_first_token: Duration,
+    pub prompt_tokens: Option<u32>,
+    // None when the provider doesn't report `prompt_tokens_details.cached_tokens`
+    pub cached_tokens: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct CacheStats {
+    pub model: String,
+    pub cold_requests: usize,
+    pub warm_requests: usize,
+    pub mean_cold_ttft: Duration,
+    pub mean_warm_ttft: Duration,
+    pub p50_cold_ttft: Duration,
+    pub p50_warm_ttft: Duration,
+    // Positive when warm requests are faster
+    pub ttft_reduction_pct: f64,
+    pub cold_cached_ratio: Option<f64>,
+    pub warm_cached_ratio: Option<f64>,
+    // Fraction of warm requests that reported any cached tokens
+    pub warm_hit_rate: Option<f64>,
+    pub goodput: Option<GoodputStats>,
+}
+
+// Cached share of all prompt tokens over samples that reported both numbers
+fn cached_ratio(samples: &[&CacheSample]) -> Option<f64> {
+    let (cached, prompt) = samples
+        .iter()
+        .filter_map(|s| Some((s.cached_tokens?, s.prompt_tokens?)))
+        .fold((0u64, 0u64), |(c, p), (cached, prompt)| (c + cached as u64, p + prompt as u64));
+    (prompt > 0).then(|| cached as f64 / prompt as f64)
+}
+
+impl CacheStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        let mut metrics = vec![
+            ("cold_requests", self.cold_requests as f64),
+            ("warm_requests", self.warm_requests as f64),
+            ("mean_cold_ttft", ms(self.mean_cold_ttft)),
+            ("mean_warm_ttft", ms(self.mean_warm_ttft)),
+            ("p50_cold_ttft", ms(self.p50_cold_ttft)),
+            ("p50_warm_ttft", ms(self.p50_warm_ttft)),
+            ("ttft_reduction_pct", self.ttft_reduction_pct),
+        ];
+        // Only when the provider reports cached tokens
+        metrics.extend(self.cold_cached_ratio.map(|ratio| ("cold_cached_ratio", ratio)));
+        metrics.extend(self.warm_cached_ratio.map(|ratio| ("warm_cached_ratio", ratio)));
+        metrics.extend(self.warm_hit_rate.map(|rate| ("warm_hit_rate", rate)));
+        metrics.extend(self.goodput.iter().flat_map(GoodputStats::history_metrics));
+        metrics
+    }
+}
+
+pub fn summarize(model: &str, samples: &[CacheSample], goodput: Option<GoodputStats>) -> CacheStats {
+    let cold: Vec<&CacheSample> = samples.iter().filter(|s| !s.warm).collect();
+    let warm: Vec<&CacheSample> = samples.iter().filter(|s| s.warm).collect();
+
+    let (mean_cold_ttft, p50_cold_ttft) = mean_and_percentile(cold.iter().map(|s| s.time_to_first_token).collect(), 50);
+    let (mean_warm_ttft, p50_warm_ttft) = mean_and_percentile(warm.iter().map(|s| s.time_to_first_token).collect(), 50);
+
+    let ttft_reduction_pct = if mean_cold_ttft.is_zero() || warm.is_empty() {
+        0.0
+    } else {
+        (1.0 - mean_warm_ttft.as_secs_f64() / mean_cold_ttft.as_secs_f64()) * 100.0
+    };
+
+    let reporting: Vec<&&CacheSample> = warm.iter().filter(|s| s.cached_tokens.is_some()).collect();
+    let warm_hit_rate = (!reporting.is_empty()).then(|| {
+        reporting.iter().filter(|s| s.cached_tokens.unwrap_or(0) > 0).count() as f64 / reporting.len() as f64
+    });
+
+    CacheStats {
+        model: model.to_string(),
+        cold_requests: cold.len(),
+        warm_requests: warm.len(),
+        mean_cold_ttft,
+        mean_warm_ttft,
+        p50_cold_ttft,
+        p50_warm_ttft,
+        ttft_reduction_pct,
+        cold_cached_ratio: cached_ratio(&cold),
+        warm_cached_ratio: cached_ratio(&warm),
+        warm_hit_rate,
+        goodput,
+    }
+}
@@ -1,37 +1,424 @@
 use anyhow::Result;
 use eventsource_stream::Eventsource;
 use futures::StreamExt;
-use reqwest::Client;
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::{Client, Response};
+use serde::Serialize;
 use serde_json::Value;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tracing::{debug, error};
+use tracing::{debug, error, warn, Instrument};
 
+use crate::cost::{BudgetExceeded, CostTracker, TokenUsage};
+use crate::limiter::RateLimiter;
 use crate::models::{
-    ChatCompletionRequest, ChatCompletionResponse, ImageGenerationRequest, ModelsResponse, StreamOptions,
+    ChatCompletionRequest, ChatCompletionResponse, EmbeddingRequest, EmbeddingResponse, FunctionCall,
+    ImageGenerationRequest, ModelsResponse, StreamOptions, ToolCall,
 };
-use crate::metrics::{LatencyMetric, StreamingMetric, ThroughputMetric};
+use crate::phases::{self, ConnectTimingLayer, PhaseRecorder, TimingResolver};
+use crate::metrics::{LatencyMetric, ReasoningMetric, StreamingMetric, ThroughputMetric};
+use crate::telemetry;
+use crate::tokenizer::{TokenSource, TokenizerRegistry};
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub api_key: String,
+    pub base_url: String,
+    // Sent as `reasoning_effort` on chat requests that don't set their own
+    pub reasoning_effort: Option<String>,
+    // Record DNS / TCP / TLS / request / server / download phases per request
+    pub phase_timing: bool,
+    pub http_version: HttpVersion,
+    pub timeouts: Timeouts,
+    // Prices every response and enforces `--max-cost`; shared by all clients of a run
+    pub cost: Arc<CostTracker>,
+    // Client-side RPM/TPM limits; shared by all clients of a run
+    pub limiter: Arc<RateLimiter>,
+}
+
+// Request deadlines. `None` disables a limit; every limit is also capped by `total`.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeouts {
+    // TCP connect (and TLS, as reqwest counts it)
+    pub connect: Option<Duration>,
+    // From sending the request to response headers
+    pub first_byte: Option<Duration>,
+    // Longest gap allowed between SSE events
+    pub idle: Option<Duration>,
+    // From sending the request to the end of the body or stream
+    pub total: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimeoutPhase {
+    Connect,
+    FirstByte,
+    Idle,
+    Total,
+}
+
+impl std::fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeoutPhase::Connect => write!(f, "connect"),
+            TimeoutPhase::FirstByte => write!(f, "first byte"),
+            TimeoutPhase::Idle => write!(f, "stream idle"),
+            TimeoutPhase::Total => write!(f, "total"),
+        }
+    }
+}
+
+impl TimeoutPhase {
+    // Metric label form
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeoutPhase::Connect => "connect",
+            TimeoutPhase::FirstByte => "first_byte",
+            TimeoutPhase::Idle => "idle",
+            TimeoutPhase::Total => "total",
+        }
+    }
+}
+
+// Returned (inside anyhow) when a deadline fires, so callers can tell timeouts from other
+// failures with `downcast_ref`. `fired_at` is measured from the start of the request.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestTimeout {
+    pub phase: TimeoutPhase,
+    pub fired_at: Duration,
+}
+
+impl std::fmt::Display for RequestTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} timeout after {:.2?}", self.phase, self.fired_at)
+    }
+}
+
+impl std::error::Error for RequestTimeout {}
+
+// A non-success HTTP response, kept typed so callers can group failures by status
+#[derive(Debug)]
+pub struct StatusError {
+    pub context: &'static str,
+    pub status: u16,
+    pub body: String,
+}
+
+impl std::fmt::Display for StatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = reqwest::StatusCode::from_u16(self.status).map_or_else(|_| self.status.to_string(), |s| s.to_string());
+        write!(f, "{} failed: {} - {}", self.context, status, self.body)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+// Groups a failed request for reporting: the HTTP status, `timeout_<phase>`, or `error` for
+// transport and other failures.
+pub fn failure_label(error: &anyhow::Error) -> String {
+    if let Some(timeout) = error.downcast_ref::<RequestTimeout>() {
+        format!("timeout_{}", timeout.phase.label())
+    } else if error.downcast_ref::<BudgetExceeded>().is_some() {
+        "budget_exceeded".to_string()
+    } else if let Some(status) = error.downcast_ref::<StatusError>() {
+        status.status.to_string()
+    } else {
+        "error".to_string()
+    }
+}
+
+// `auto` lets TLS ALPN pick (HTTP/2 when the server offers it); the others force a protocol.
+// Forcing HTTP/2 on a plain-HTTP URL uses prior knowledge (h2c).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HttpVersion {
+    Auto,
+    Http1,
+    Http2,
+}
+
+impl HttpVersion {
+    pub fn alpn_protocols(self) -> Vec<Vec<u8>> {
+        match self {
+            HttpVersion::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+            HttpVersion::Http1 => vec![b"http/1.1".to_vec()],
+            HttpVersion::Http2 => vec![b"h2".to_vec()],
+        }
+    }
+}
+
+impl std::fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpVersion::Auto => write!(f, "auto"),
+            HttpVersion::Http1 => write!(f, "http1"),
+            HttpVersion::Http2 => write!(f, "http2"),
+        }
+    }
+}
+
+// How requests get HTTP connections: `warm` shares one pooled client, `per-worker` gives
+// each concurrent worker its own client (and so its own connections), `cold` builds a new
+// client, and therefore a new connection, for every request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConnectionMode {
+    Cold,
+    Warm,
+    PerWorker,
+}
+
+impl std::fmt::Display for ConnectionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionMode::Cold => write!(f, "cold"),
+            ConnectionMode::Warm => write!(f, "warm"),
+            ConnectionMode::PerWorker => write!(f, "per-worker"),
+        }
+    }
+}
+
+// Hands out clients according to a `ConnectionMode`. Check a client out after taking a
+// concurrency permit so per-worker clients map onto workers.
+pub struct ClientPool {
+    mode: ConnectionMode,
+    config: ClientConfig,
+    tokenizers: Arc<TokenizerRegistry>,
+    shared: Arc<SudoClient>,
+    idle: Mutex<Vec<Arc<SudoClient>>>,
+}
+
+impl ClientPool {
+    pub fn new(mode: ConnectionMode, config: ClientConfig, tokenizers: Arc<TokenizerRegistry>) -> Self {
+        let shared = Arc::new(SudoClient::new(config.clone(), Arc::clone(&tokenizers)));
+        Self {
+            mode,
+            config,
+            tokenizers,
+            shared,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn mode(&self) -> ConnectionMode {
+        self.mode
+    }
+
+    // A separate pool with the same client settings, so modes can be compared side by side
+    pub fn with_mode(&self, mode: ConnectionMode) -> Self {
+        Self::new(mode, self.config.clone(), Arc::clone(&self.tokenizers))
+    }
+
+    // A separate pool forcing `version`, keeping the connection mode and other settings
+    pub fn with_http_version(&self, version: HttpVersion) -> Self {
+        let config = ClientConfig {
+            http_version: version,
+            ..self.config.clone()
+        };
+        Self::new(self.mode, config, Arc::clone(&self.tokenizers))
+    }
+
+    pub fn tokenizers(&self) -> &TokenizerRegistry {
+        &self.tokenizers
+    }
+
+    fn build(&self) -> Arc<SudoClient> {
+        Arc::new(SudoClient::new(self.config.clone(), Arc::clone(&self.tokenizers)))
+    }
+
+    pub fn checkout(&self) -> ClientLease<'_> {
+        let client = match self.mode {
+            ConnectionMode::Warm => Arc::clone(&self.shared),
+            ConnectionMode::Cold => self.build(),
+            ConnectionMode::PerWorker => self.idle.lock().unwrap().pop().unwrap_or_else(|| self.build()),
+        };
+        ClientLease {
+            pool: self,
+            client: Some(client),
+        }
+    }
+}
+
+// A checked-out client; per-worker clients go back to the pool on drop.
+pub struct ClientLease<'a> {
+    pool: &'a ClientPool,
+    client: Option<Arc<SudoClient>>,
+}
+
+impl std::ops::Deref for ClientLease<'_> {
+    type Target = SudoClient;
+
+    fn deref(&self) -> &SudoClient {
+        self.client.as_ref().expect("client lease already returned")
+    }
+}
+
+impl Drop for ClientLease<'_> {
+    fn drop(&mut self) {
+        if self.pool.mode == ConnectionMode::PerWorker {
+            if let Some(client) = self.client.take() {
+                self.pool.idle.lock().unwrap().push(client);
+            }
+        }
+    }
+}
 
 pub struct SudoClient {
     client: Client,
     api_key: String,
     base_url: String,
+    reasoning_effort: Option<String>,
+    phase_timing: bool,
+    timeouts: Timeouts,
+    tokenizers: Arc<TokenizerRegistry>,
+    cost: Arc<CostTracker>,
+    limiter: Arc<RateLimiter>,
 }
 
 impl SudoClient {
-    pub fn new(api_key: String, base_url: String) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(120))
+    pub fn new(config: ClientConfig, tokenizers: Arc<TokenizerRegistry>) -> Self {
+        // The total deadline is enforced per request so the phase that ran out can be reported
+        let mut builder = Client::builder()
             // Encourage connection reuse and reduce setup overhead under concurrency
             .pool_max_idle_per_host(32)
-            .pool_idle_timeout(Duration::from_secs(90))
-            .build()
-            .expect("Failed to create HTTP client");
+            .pool_idle_timeout(Duration::from_secs(90));
+        if let Some(connect) = config.timeouts.connect {
+            builder = builder.connect_timeout(connect);
+        }
+        builder = match config.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        };
+        if config.phase_timing {
+            builder = builder
+                .dns_resolver(Arc::new(TimingResolver))
+                .connector_layer(ConnectTimingLayer)
+                .use_preconfigured_tls(
+                    phases::tls_config(config.http_version.alpn_protocols()).expect("Failed to create TLS config"),
+                );
+        }
+        let client = builder.build().expect("Failed to create HTTP client");
 
         Self {
             client,
-            api_key,
-            base_url,
+            api_key: config.api_key,
+            base_url: config.base_url,
+            reasoning_effort: config.reasoning_effort,
+            phase_timing: config.phase_timing,
+            timeouts: config.timeouts,
+            tokenizers,
+            cost: config.cost,
+            limiter: config.limiter,
+        }
+    }
+
+    // POSTs `body` as JSON, failing with a `RequestTimeout` if the connect or first-byte
+    // deadline (counted from `start`) passes before response headers arrive. With phase
+    // timing on, the send runs under a recorder that the resolver, connector and TLS hooks
+    // report into.
+    async fn post_json<T: Serialize>(
+        &self,
+        url: &str,
+        body: &T,
+        start: Instant,
+    ) -> Result<(Response, Option<Arc<PhaseRecorder>>)> {
+        let body = serde_json::to_vec(body)?;
+        let builder = self
+            .client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .headers(telemetry::trace_headers());
+
+        let (phase, limit) = match self.timeouts.first_byte {
+            Some(first_byte) if first_byte < self.timeouts.total => (TimeoutPhase::FirstByte, first_byte),
+            _ => (TimeoutPhase::Total, self.timeouts.total),
+        };
+        let limit = limit.saturating_sub(start.elapsed());
+
+        let (sent, recorder) = if self.phase_timing {
+            let recorder = PhaseRecorder::new();
+            let send = builder
+                .header(CONTENT_LENGTH, body.len())
+                .body(recorder.timed_body(body))
+                .send();
+            (tokio::time::timeout(limit, recorder.scope(send)).await, Some(recorder))
+        } else {
+            (tokio::time::timeout(limit, builder.body(body).send()).await, None)
+        };
+
+        match sent {
+            Ok(Ok(response)) => {
+                let span = tracing::Span::current();
+                span.record("http.status_code", response.status().as_u16());
+                if let Some(id) = response
+                    .headers()
+                    .get("x-request-id")
+                    .or_else(|| response.headers().get("request-id"))
+                    .and_then(|id| id.to_str().ok())
+                {
+                    span.record("gateway.request_id", id);
+                }
+                Ok((response, recorder))
+            }
+            Ok(Err(e)) if e.is_connect() && e.is_timeout() => Err(RequestTimeout {
+                phase: TimeoutPhase::Connect,
+                fired_at: start.elapsed(),
+            }
+            .into()),
+            Ok(Err(e)) => Err(e.into()),
+            Err(_) => Err(RequestTimeout {
+                phase,
+                fired_at: start.elapsed(),
+            }
+            .into()),
+        }
+    }
+
+    // Runs `future` (reading a response body) within what's left of the total deadline
+    async fn within_total<F: std::future::Future>(&self, start: Instant, future: F) -> Result<F::Output> {
+        tokio::time::timeout(self.timeouts.total.saturating_sub(start.elapsed()), future)
+            .await
+            .map_err(|_| {
+                RequestTimeout {
+                    phase: TimeoutPhase::Total,
+                    fired_at: start.elapsed(),
+                }
+                .into()
+            })
+    }
+
+    // The next SSE event, failing if the idle gap or the total deadline runs out first
+    async fn next_event<S: futures::Stream + Unpin>(&self, stream: &mut S, start: Instant) -> Result<Option<S::Item>> {
+        let remaining = self.timeouts.total.saturating_sub(start.elapsed());
+        let (phase, limit) = match self.timeouts.idle {
+            Some(idle) if idle < remaining => (TimeoutPhase::Idle, idle),
+            _ => (TimeoutPhase::Total, remaining),
+        };
+        tokio::time::timeout(limit, stream.next()).await.map_err(|_| {
+            RequestTimeout {
+                phase,
+                fired_at: start.elapsed(),
+            }
+            .into()
+        })
+    }
+
+    // Prompt tokens counted locally and the most the reply may use, for the tokens/min limits
+    // and the cost budget
+    fn estimate_tokens(&self, request: &ChatCompletionRequest) -> (u32, u32) {
+        if !self.limiter.is_enabled() && !self.cost.has_budget() {
+            return (0, 0);
+        }
+        let (prompt, _) = self.tokenizers.count_prompt(&request.model, &request.messages);
+        (prompt, prompt + request.max_completion_tokens.unwrap_or(0))
+    }
+
+    fn with_defaults(&self, request: &ChatCompletionRequest) -> ChatCompletionRequest {
+        let mut request = request.clone();
+        if request.reasoning_effort.is_none() {
+            request.reasoning_effort = self.reasoning_effort.clone();
         }
+        request
     }
 
     pub async fn get_models(&self) -> Result<ModelsResponse> {
@@ -42,6 +429,7 @@ impl SudoClient {
             .get(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
+            .timeout(self.timeouts.total)
             .send()
             .await?;
 
@@ -62,84 +450,126 @@ impl SudoClient {
     pub async fn create_chat_completion(
         &self,
         request: &ChatCompletionRequest,
+    ) -> Result<(ChatCompletionResponse, LatencyMetric)> {
+        traced("chat_completion", &request.model, self.chat_completion(request)).await
+    }
+
+    pub async fn create_streaming_chat_completion(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<StreamingMetric> {
+        traced("streaming_chat_completion", &request.model, self.streaming_chat_completion(request)).await
+    }
+
+    pub async fn create_embeddings(
+        &self,
+        request: &EmbeddingRequest,
+    ) -> Result<(EmbeddingResponse, LatencyMetric)> {
+        traced("embeddings", &request.model, self.embeddings(request)).await
+    }
+
+    async fn chat_completion(
+        &self,
+        request: &ChatCompletionRequest,
     ) -> Result<(ChatCompletionResponse, LatencyMetric)> {
         let url = format!("{}/v1/chat/completions", self.base_url);
+        let request = &self.with_defaults(request);
+        let (prompt_estimate, estimate) = self.estimate_tokens(request);
+        let limiter_wait = self.limiter.acquire(&request.model, estimate).await;
+        let reservation = self.cost.reserve(&request.model, prompt_estimate, request.max_completion_tokens)?;
         let start_time = Instant::now();
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(request)
-            .send()
-            .await?;
+        let (response, recorder) = self.post_json(&url, request, start_time).await?;
 
         let headers_received = Instant::now();
+        let http_version = format!("{:?}", response.version());
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!(
-                "Chat completion failed: {} - {}",
-                status,
-                text
-            ));
+            return Err(StatusError {
+                context: "Chat completion",
+                status: status.as_u16(),
+                body: text,
+            }
+            .into());
         }
 
-        let completion: ChatCompletionResponse = response.json().await?;
+        let completion: ChatCompletionResponse = self.within_total(start_time, response.json()).await??;
         let end_time = Instant::now();
+        if let Some(tokens) = completion.usage.as_ref().and_then(|u| u.completion_tokens) {
+            tracing::Span::current().record("tokens", tokens);
+        }
+        reservation.settle(&request.model, completion.usage.as_ref().map(TokenUsage::from), true);
+        if let Some(usage) = &completion.usage {
+            let actual = usage.prompt_tokens + usage.completion_tokens.unwrap_or(0);
+            self.limiter.settle_tokens(&request.model, estimate, actual);
+        }
 
         let metric = LatencyMetric {
             total_duration: end_time.duration_since(start_time),
             time_to_first_byte: headers_received.duration_since(start_time),
             request_size: serde_json::to_vec(request)?.len(),
             response_size: serde_json::to_vec(&completion)?.len(),
+            phases: recorder.map(|r| r.finish(headers_received, end_time)),
+            http_version,
             model: request.model.clone(),
+            limiter_wait,
         };
 
         Ok((completion, metric))
     }
 
-    pub async fn create_streaming_chat_completion(
+    async fn streaming_chat_completion(
         &self,
         request: &ChatCompletionRequest,
     ) -> Result<StreamingMetric> {
         let url = format!("{}/v1/chat/completions", self.base_url);
+        let (prompt_estimate, estimate) = self.estimate_tokens(request);
+        let limiter_wait = self.limiter.acquire(&request.model, estimate).await;
+        let reservation = self.cost.reserve(&request.model, prompt_estimate, request.max_completion_tokens)?;
         let start_time = Instant::now();
 
         // Create streaming request
-        let mut streaming_request = request.clone();
+        let mut streaming_request = self.with_defaults(request);
         streaming_request.stream = Some(true);
         // Request accurate usage reporting in the stream if supported
         streaming_request.stream_options = Some(StreamOptions { include_usage: true });
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&streaming_request)
-            .send()
-            .await?;
+        let (response, recorder) = self.post_json(&url, &streaming_request, start_time).await?;
+        let headers_received = Instant::now();
+        let http_version = format!("{:?}", response.version());
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!(
-                "Streaming chat completion failed: {} - {}",
-                status,
-                text
-            ));
+            return Err(StatusError {
+                context: "Streaming chat completion",
+                status: status.as_u16(),
+                body: text,
+            }
+            .into());
         }
 
         let mut metric = StreamingMetric {
             total_duration: Duration::new(0, 0),
+            time_to_first_byte: headers_received.duration_since(start_time),
             time_to_first_chunk: None,
             chunk_count: 0,
             total_tokens: 0,
+            token_source: TokenSource::Heuristic,
+            usage: None,
+            output_text: String::new(),
+            finish_reason: None,
+            tool_calls: Vec::new(),
+            time_to_first_tool_call: None,
+            time_to_tool_call_complete: None,
+            reasoning: None,
+            phases: None,
+            http_version,
             model: request.model.clone(),
             request_size: serde_json::to_vec(&streaming_request)?.len(),
+            limiter_wait,
         };
 
         // Process the streaming response
@@ -148,8 +578,12 @@ impl SudoClient {
 
         let mut first_chunk_received = false;
         let mut usage_completion_tokens: Option<u32> = None;
+        let mut output_text = String::new();
+        let mut reasoning_text = String::new();
+        let mut time_to_first_reasoning: Option<Duration> = None;
+        let mut time_to_first_content: Option<Duration> = None;
 
-        while let Some(event_result) = stream.next().await {
+        while let Some(event_result) = self.next_event(&mut stream, start_time).await? {
             match event_result {
                 Ok(event) => {
                     debug!("Received streaming event: type={}, data={}", event.event, event.data);
@@ -158,6 +592,9 @@ impl SudoClient {
                         metric.time_to_first_chunk = Some(Instant::now().duration_since(start_time));
                         first_chunk_received = true;
                         debug!("First streaming chunk received: {:?}", metric.time_to_first_chunk);
+                        let ttfc_ms = metric.time_to_first_chunk.unwrap_or_default().as_secs_f64() * 1000.0;
+                        tracing::Span::current().record("ttfc_ms", ttfc_ms);
+                        debug!(target: telemetry::REQUEST_TARGET, ttfc_ms, "first chunk");
                     }
 
                     metric.chunk_count += 1;
@@ -174,17 +611,42 @@ impl SudoClient {
                             for choice in choices {
                                 if let Some(delta) = choice.get("delta").and_then(|d| d.as_object()) {
                                     if let Some(content) = delta.get("content").and_then(|c| c.as_str()) {
-                                        // Rough token estimation: ~4 characters per token
-                                        metric.total_tokens += (content.len() as f32 / 4.0).ceil() as u32;
+                                        if !content.is_empty() {
+                                            time_to_first_content.get_or_insert_with(|| start_time.elapsed());
+                                        }
+                                        output_text.push_str(content);
+                                    }
+                                    // DeepSeek and most gateways use `reasoning_content`; some use `reasoning`
+                                    let reasoning = delta
+                                        .get("reasoning_content")
+                                        .or_else(|| delta.get("reasoning"))
+                                        .and_then(|r| r.as_str());
+                                    if let Some(reasoning) = reasoning.filter(|r| !r.is_empty()) {
+                                        time_to_first_reasoning.get_or_insert_with(|| start_time.elapsed());
+                                        reasoning_text.push_str(reasoning);
+                                    }
+                                    if let Some(deltas) = delta.get("tool_calls").and_then(|t| t.as_array()) {
+                                        let elapsed = Instant::now().duration_since(start_time);
+                                        metric.time_to_first_tool_call.get_or_insert(elapsed);
+                                        // Arguments are complete once the last tool-call delta has arrived
+                                        metric.time_to_tool_call_complete = Some(elapsed);
+                                        merge_tool_call_deltas(&mut metric.tool_calls, deltas);
                                     }
                                 }
+                                if let Some(reason) = choice.get("finish_reason").and_then(|r| r.as_str()) {
+                                    metric.finish_reason = Some(reason.to_string());
+                                }
                             }
                         }
                         // Prefer precise usage if provided in a final event
-                        if let Some(usage) = data.get("usage").and_then(|u| u.as_object()) {
+                        if let Some(usage) = data.get("usage").filter(|u| u.is_object()) {
                             if let Some(ct) = usage.get("completion_tokens").and_then(|v| v.as_u64()) {
                                 usage_completion_tokens = Some(ct as u32);
                             }
+                            match serde_json::from_value(usage.clone()) {
+                                Ok(parsed) => metric.usage = Some(parsed),
+                                Err(e) => warn!("Unparseable usage chunk from {}: {} ({})", request.model, e, usage),
+                            }
                         }
                     } else {
                         debug!("Failed to parse streaming event data as JSON: {}", event.data);
@@ -197,12 +659,47 @@ impl SudoClient {
             }
         }
 
-        metric.total_duration = Instant::now().duration_since(start_time);
+        let end_time = Instant::now();
+        metric.total_duration = end_time.duration_since(start_time);
+        metric.phases = recorder.map(|r| r.finish(headers_received, end_time));
+        // Billed whether or not a chunk arrived
+        let usage = metric.usage.as_ref().map(TokenUsage::from);
+        reservation.settle(&request.model, usage, metric.time_to_first_chunk.is_some());
 
-        // If the server provided exact usage, use it instead of heuristic.
+        // If the server provided exact usage, use it; otherwise count the streamed text locally.
+        // Either way the count includes reasoning tokens.
+        let local_reasoning_tokens = (!reasoning_text.is_empty())
+            .then(|| self.tokenizers.count(&request.model, &reasoning_text).0);
         if let Some(ct) = usage_completion_tokens {
             metric.total_tokens = ct;
+            metric.token_source = TokenSource::Usage;
+        } else {
+            let (tokens, source) = self.tokenizers.count(&request.model, &output_text);
+            metric.total_tokens = tokens + local_reasoning_tokens.unwrap_or(0);
+            metric.token_source = source;
+        }
+        let actual_tokens = match &metric.usage {
+            Some(usage) => usage.prompt_tokens + usage.completion_tokens.unwrap_or(metric.total_tokens),
+            None => prompt_estimate + metric.total_tokens,
+        };
+        self.limiter.settle_tokens(&request.model, estimate, actual_tokens);
+
+        let reasoning_tokens = metric
+            .usage
+            .as_ref()
+            .and_then(|u| u.reasoning_tokens())
+            .filter(|&t| t > 0)
+            .or(local_reasoning_tokens);
+        if let Some(reasoning_tokens) = reasoning_tokens {
+            metric.reasoning = Some(reasoning_metric(
+                &metric,
+                reasoning_tokens,
+                time_to_first_reasoning,
+                time_to_first_content,
+            ));
         }
+        metric.output_text = output_text;
+        tracing::Span::current().record("tokens", metric.total_tokens);
 
         if metric.time_to_first_chunk.is_none() {
             return Err(anyhow::anyhow!("No streaming chunks received for model {}. Chunk count: {}, Total duration: {:?}", request.model, metric.chunk_count, metric.total_duration));
@@ -211,6 +708,63 @@ impl SudoClient {
         Ok(metric)
     }
 
+    async fn embeddings(
+        &self,
+        request: &EmbeddingRequest,
+    ) -> Result<(EmbeddingResponse, LatencyMetric)> {
+        let url = format!("{}/v1/embeddings", self.base_url);
+        let estimate = if self.limiter.is_enabled() || self.cost.has_budget() {
+            request.input.iter().map(|input| self.tokenizers.count(&request.model, input).0).sum()
+        } else {
+            0
+        };
+        let limiter_wait = self.limiter.acquire(&request.model, estimate).await;
+        let reservation = self.cost.reserve(&request.model, estimate, Some(0))?;
+        let start_time = Instant::now();
+
+        let (response, recorder) = self.post_json(&url, request, start_time).await?;
+
+        let headers_received = Instant::now();
+        let http_version = format!("{:?}", response.version());
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(StatusError {
+                context: "Embeddings request",
+                status: status.as_u16(),
+                body: text,
+            }
+            .into());
+        }
+
+        let body = self.within_total(start_time, response.bytes()).await??;
+        let end_time = Instant::now();
+        let embeddings: EmbeddingResponse = serde_json::from_slice(&body)?;
+        if let Some(usage) = &embeddings.usage {
+            tracing::Span::current().record("tokens", usage.prompt_tokens);
+            self.limiter.settle_tokens(&request.model, estimate, usage.prompt_tokens);
+        }
+        let usage = embeddings.usage.as_ref().map(|u| TokenUsage {
+            input: u.prompt_tokens,
+            ..TokenUsage::default()
+        });
+        reservation.settle(&request.model, usage, true);
+
+        let metric = LatencyMetric {
+            total_duration: end_time.duration_since(start_time),
+            time_to_first_byte: headers_received.duration_since(start_time),
+            request_size: serde_json::to_vec(request)?.len(),
+            response_size: body.len(),
+            phases: recorder.map(|r| r.finish(headers_received, end_time)),
+            http_version,
+            model: request.model.clone(),
+            limiter_wait,
+        };
+
+        Ok((embeddings, metric))
+    }
+
     #[allow(dead_code)]
     pub async fn generate_image(
         &self,
@@ -225,19 +779,22 @@ impl SudoClient {
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
             .json(request)
+            .timeout(self.timeouts.total)
             .send()
             .await?;
 
         let headers_received = Instant::now();
+        let http_version = format!("{:?}", response.version());
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!(
-                "Image generation failed: {} - {}",
-                status,
-                text
-            ));
+            return Err(StatusError {
+                context: "Image generation",
+                status: status.as_u16(),
+                body: text,
+            }
+            .into());
         }
 
         let _image_response: Value = response.json().await?;
@@ -248,12 +805,16 @@ impl SudoClient {
             time_to_first_byte: headers_received.duration_since(start_time),
             request_size: serde_json::to_vec(request)?.len(),
             response_size: 0, // We don't measure image response size
+            phases: None,
+            http_version,
             model: request.model.clone(),
+            limiter_wait: Duration::ZERO,
         };
 
         Ok(metric)
     }
 
+    #[allow(dead_code)]
     pub async fn single_request_throughput_test(
         &self,
         request: &ChatCompletionRequest,
@@ -261,15 +822,24 @@ impl SudoClient {
         let start_time = Instant::now();
         
         match self.create_chat_completion(request).await {
-            Ok((response, _)) => {
+            Ok((response, latency_metric)) => {
                 let end_time = Instant::now();
-                let duration = end_time.duration_since(start_time);
+                // Time waiting in the rate limiter isn't part of the request
+                let duration = end_time.duration_since(start_time).saturating_sub(latency_metric.limiter_wait);
                 
-                let tokens = if let Some(usage) = response.usage {
-                    usage.completion_tokens.unwrap_or(0) as f64
-                } else {
-                    0.0
+                let (tokens, token_source) = match response.usage.as_ref().and_then(|u| u.completion_tokens) {
+                    Some(ct) => (ct, TokenSource::Usage),
+                    None => {
+                        let text: String = response
+                            .choices
+                            .iter()
+                            .filter_map(|c| c.message.as_ref())
+                            .map(|m| m.text())
+                            .collect();
+                        self.tokenizers.count(&request.model, &text)
+                    }
                 };
+                let tokens = tokens as f64;
 
                 let tokens_per_second = if duration.as_secs_f64() > 0.0 {
                     tokens / duration.as_secs_f64()
@@ -279,11 +849,18 @@ impl SudoClient {
 
                 Ok(ThroughputMetric {
                     duration,
+                    time_to_first_chunk: None,
+                    tokens: tokens as u32,
                     successful_requests: 1,
                     failed_requests: 0,
                     tokens_per_second,
                     requests_per_second: 1.0 / duration.as_secs_f64(),
+                    token_source: Some(token_source),
+                    reasoning: None,
+                    timeout: None,
+                    failure: None,
                     model: request.model.clone(),
+                    limiter_wait: latency_metric.limiter_wait,
                 })
             }
             Err(e) => {
@@ -292,11 +869,18 @@ impl SudoClient {
                 
                 Ok(ThroughputMetric {
                     duration,
+                    time_to_first_chunk: None,
+                    tokens: 0,
                     successful_requests: 0,
                     failed_requests: 1,
                     tokens_per_second: 0.0,
                     requests_per_second: 0.0,
+                    token_source: None,
+                    reasoning: None,
+                    timeout: e.downcast_ref::<RequestTimeout>().copied(),
+                    failure: Some(failure_label(&e)),
                     model: request.model.clone(),
+                    limiter_wait: Duration::ZERO,
                 })
             }
         }
@@ -325,12 +909,19 @@ impl SudoClient {
 
                 Ok(ThroughputMetric {
                     duration: generation_duration,
+                    time_to_first_chunk: streaming_metric.time_to_first_chunk,
+                    tokens: streaming_metric.total_tokens,
                     successful_requests: 1,
                     failed_requests: 0,
                     tokens_per_second,
                     // For completeness, base RPS on end-to-end duration
                     requests_per_second: if streaming_metric.total_duration.as_secs_f64() > 0.0 { 1.0 / streaming_metric.total_duration.as_secs_f64() } else { 0.0 },
+                    token_source: Some(streaming_metric.token_source),
+                    reasoning: streaming_metric.reasoning,
+                    timeout: None,
+                    failure: None,
                     model: request.model.clone(),
+                    limiter_wait: streaming_metric.limiter_wait,
                 })
             }
             Err(e) => {
@@ -338,13 +929,155 @@ impl SudoClient {
                 
                 Ok(ThroughputMetric {
                     duration: Duration::from_secs(0),
+                    time_to_first_chunk: None,
+                    tokens: 0,
                     successful_requests: 0,
                     failed_requests: 1,
                     tokens_per_second: 0.0,
                     requests_per_second: 0.0,
+                    token_source: None,
+                    reasoning: None,
+                    timeout: e.downcast_ref::<RequestTimeout>().copied(),
+                    failure: Some(failure_label(&e)),
                     model: request.model.clone(),
+                    limiter_wait: Duration::ZERO,
                 })
             }
         }
     }
 }
+
+// Runs one API call inside a request span. The span and its events go to the OTLP exporter
+// when one is configured; fields are filled in as the request progresses.
+async fn traced<T>(operation: &'static str, model: &str, call: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+    let span = tracing::debug_span!(
+        target: telemetry::REQUEST_TARGET,
+        "request",
+        otel.name = operation,
+        otel.status_code = tracing::field::Empty,
+        model,
+        http.status_code = tracing::field::Empty,
+        gateway.request_id = tracing::field::Empty,
+        ttfc_ms = tracing::field::Empty,
+        tokens = tracing::field::Empty,
+        // Requests are never retried
+        retry_count = 0,
+        error = tracing::field::Empty,
+    );
+
+    let result = call.instrument(span.clone()).await;
+    match &result {
+        Ok(_) => {
+            span.record("otel.status_code", "OK");
+            debug!(target: telemetry::REQUEST_TARGET, parent: &span, "completed");
+        }
+        Err(e) => {
+            span.record("otel.status_code", "ERROR");
+            span.record("error", tracing::field::display(e));
+            debug!(target: telemetry::REQUEST_TARGET, parent: &span, error = %e, "failed");
+        }
+    }
+    result
+}
+
+// Thinking runs from the first reasoning delta, or from the first chunk when the provider
+// hides its reasoning, until the first visible content.
+fn reasoning_metric(
+    metric: &StreamingMetric,
+    reasoning_tokens: u32,
+    time_to_first_reasoning: Option<Duration>,
+    time_to_first_content: Option<Duration>,
+) -> ReasoningMetric {
+    let thinking_start = time_to_first_reasoning.or(metric.time_to_first_chunk).unwrap_or_default();
+    let thinking_end = time_to_first_content.unwrap_or(metric.total_duration);
+    let visible_tokens = metric.total_tokens.saturating_sub(reasoning_tokens);
+    let visible_duration = time_to_first_content.map_or(Duration::from_secs(0), |t| metric.total_duration.saturating_sub(t));
+
+    ReasoningMetric {
+        reasoning_tokens,
+        thinking_duration: thinking_end.saturating_sub(thinking_start),
+        time_to_first_visible_token: time_to_first_content,
+        visible_tokens,
+        visible_tokens_per_second: if visible_duration.is_zero() {
+            0.0
+        } else {
+            visible_tokens as f64 / visible_duration.as_secs_f64()
+        },
+    }
+}
+
+// Tool calls stream as fragments keyed by `index`: the first carries the id and function
+// name, later ones append to the JSON arguments string.
+fn merge_tool_call_deltas(tool_calls: &mut Vec<ToolCall>, deltas: &[Value]) {
+    for delta in deltas {
+        let index = delta
+            .get("index")
+            .and_then(|i| i.as_u64())
+            .map(|i| i as usize)
+            .unwrap_or(tool_calls.len().saturating_sub(1));
+
+        while tool_calls.len() <= index {
+            tool_calls.push(ToolCall {
+                id: String::new(),
+                kind: "function".to_string(),
+                function: FunctionCall {
+                    name: String::new(),
+                    arguments: String::new(),
+                },
+            });
+        }
+
+        let call = &mut tool_calls[index];
+        if let Some(id) = delta.get("id").and_then(|v| v.as_str()) {
+            call.id = id.to_string();
+        }
+        if let Some(function) = delta.get("function") {
+            if let Some(name) = function.get("name").and_then(|v| v.as_str()) {
+                call.function.name.push_str(name);
+            }
+            if let Some(arguments) = function.get("arguments").and_then(|v| v.as_str()) {
+                call.function.arguments.push_str(arguments);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tool_call_fragments_merge_by_index() {
+        let mut tool_calls = Vec::new();
+        let chunks = [
+            json!([{"index": 0, "id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": ""}}]),
+            json!([{"index": 0, "function": {"arguments": "{\"city\":"}}]),
+            json!([
+                {"index": 0, "function": {"arguments": "\"Paris\"}"}},
+                {"index": 1, "id": "call_2", "function": {"name": "get_time", "arguments": "{}"}}
+            ]),
+        ];
+        for chunk in &chunks {
+            merge_tool_call_deltas(&mut tool_calls, chunk.as_array().unwrap());
+        }
+
+        assert_eq!(tool_calls.len(), 2);
+        assert_eq!(tool_calls[0].id, "call_1");
+        assert_eq!(tool_calls[0].function.name, "get_weather");
+        assert_eq!(tool_calls[0].function.arguments, "{\"city\":\"Paris\"}");
+        assert_eq!(tool_calls[1].id, "call_2");
+        assert_eq!(tool_calls[1].function.name, "get_time");
+    }
+
+    #[test]
+    fn fragments_without_an_index_extend_the_last_call() {
+        let mut tool_calls = Vec::new();
+        merge_tool_call_deltas(&mut tool_calls, &[json!({"id": "call_1", "function": {"name": "get_weather"}})]);
+        merge_tool_call_deltas(&mut tool_calls, &[json!({"function": {"arguments": "{}"}})]);
+
+        assert_eq!(tool_calls.len(), 1);
+        assert_eq!(tool_calls[0].kind, "function");
+        assert_eq!(tool_calls[0].function.arguments, "{}");
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::metrics::{count_http_versions, mean_duration, ms, percentile, GoodputStats};
+use crate::phases::PhaseTimings;
+
+#[derive(Debug, Clone)]
+pub struct ConnectionSample {
+    pub time_to_first_token: Duration,
+    pub total_duration: Duration,
+    pub http_version: String,
+    // Present with --phase-timing
+    pub phases: Option<PhaseTimings>,
+}
+
+// One transport setting (a connection mode or an HTTP version) run over the shared workload
+#[derive(Debug)]
+pub struct ConnectionModeStats {
+    pub label: String,
+    pub requests: usize,
+    pub failed_requests: usize,
+    pub mean_ttft: Duration,
+    pub p50_ttft: Duration,
+    pub p95_ttft: Duration,
+    pub mean_total_duration: Duration,
+    pub p95_total_duration: Duration,
+    // Successful requests over the wall-clock time of the whole run
+    pub requests_per_second: f64,
+    // Negotiated protocol -> requests
+    pub http_versions: BTreeMap<String, usize>,
+    // Only known with --phase-timing
+    pub new_connections: Option<usize>,
+    // Mean DNS + TCP connect + TLS handshake over requests that opened a connection
+    pub mean_connection_setup: Option<Duration>,
+    pub goodput: Option<GoodputStats>,
+}
+
+impl ConnectionModeStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        let mut metrics = vec![
+            ("requests", self.requests as f64),
+            ("failed_requests", self.failed_requests as f64),
+            ("mean_ttft", ms(self.mean_ttft)),
+            ("p50_ttft", ms(self.p50_ttft)),
+            ("p95_ttft", ms(self.p95_ttft)),
+            ("mean_total_duration", ms(self.mean_total_duration)),
+            ("p95_total_duration", ms(self.p95_total_duration)),
+            ("requests_per_second", self.requests_per_second),
+        ];
+        metrics.extend(self.new_connections.map(|count| ("new_connections", count as f64)));
+        metrics.extend(self.mean_connection_setup.map(|setup| ("mean_connection_setup", ms(setup))));
+        metrics.extend(self.goodput.iter().flat_map(GoodputStats::history_metrics));
+        metrics
+    }
+}
+
+#[derive(Debug)]
+pub struct ConnectionComparison {
+    pub model: String,
+    // The first row is the baseline the others are compared against
+    pub modes: Vec<ConnectionModeStats>,
+}
+
+impl ConnectionComparison {
+    // Mean TTFT added by `stats` over the baseline row
+    pub fn ttft_overhead(&self, stats: &ConnectionModeStats) -> Option<f64> {
+        let baseline = self.modes.first()?;
+        Some((stats.mean_ttft.as_secs_f64() - baseline.mean_ttft.as_secs_f64()) * 1000.0)
+    }
+}
+
+pub fn summarize(
+    label: &str,
+    samples: &[ConnectionSample],
+    failed_requests: usize,
+    wall_time: Duration,
+    goodput: Option<GoodputStats>,
+) -> ConnectionModeStats {
+    let mut ttfts: Vec<Duration> = samples.iter().map(|s| s.time_to_first_token).collect();
+    ttfts.sort();
+    let mut totals: Vec<Duration> = samples.iter().map(|s| s.total_duration).collect();
+    totals.sort();
+
+    let phases: Vec<&PhaseTimings> = samples.iter().filter_map(|s| s.phases.as_ref()).collect();
+    let setups: Vec<Duration> = phases
+        .iter()
+        .filter(|p| !p.reused_connection)
+        .map(|p| p.dns.unwrap_or_default() + p.tcp_connect.unwrap_or_default() + p.tls_handshake.unwrap_or_default())
+        .collect();
+
+    ConnectionModeStats {
+        label: label.to_string(),
+        requests: samples.len(),
+        failed_requests,
+        mean_ttft: mean_duration(&ttfts),
+        p50_ttft: percentile(&ttfts, 50),
+        p95_ttft: percentile(&ttfts, 95),
+        mean_total_duration: mean_duration(&totals),
+        p95_total_duration: percentile(&totals, 95),
+        requests_per_second: if wall_time.is_zero() { 0.0 } else { samples.len() as f64 / wall_time.as_secs_f64() },
+        http_versions: count_http_versions(samples.iter().map(|s| s.http_version.as_str())),
+        new_connections: (!phases.is_empty()).then_some(setups.len()),
+        mean_connection_setup: (!setups.is_empty()).then(|| mean_duration(&setups)),
+        goodput,
+    }
+}
@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use crate::metrics::{linear_fit, mean_duration, ms, percentile, GoodputSample, GoodputStats, LinearFit};
+use crate::models::{ChatCompletionRequest, ChatMessage};
+
+const OPENING_PROMPT: &str =
+    "I'm planning a week-long trip to Japan in spring. Can you suggest a rough itinerary?";
+
+// Follow-ups cycle if a conversation has more turns than there are entries
+const FOLLOW_UPS: &[&str] = &[
+    "Which of those days would you cut if I only had five days?",
+    "What should I budget per day for food and local transport?",
+    "Can you suggest a vegetarian-friendly restaurant area for each city?",
+    "How do I get from the airport to the first stop?",
+    "What etiquette mistakes should I avoid on trains and in temples?",
+    "Rewrite the itinerary as a compact day-by-day checklist.",
+    "Which items on that list need advance booking?",
+    "Summarize everything we've discussed in five bullet points.",
+];
+
+// A chat that grows by one user message and the model's actual reply per turn.
+pub struct Conversation {
+    model: String,
+    messages: Vec<ChatMessage>,
+    turn: usize,
+}
+
+impl Conversation {
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            messages: Vec::new(),
+            turn: 0,
+        }
+    }
+
+    // Appends the next user message and returns the request for this turn.
+    pub fn next_request(&mut self, streaming: bool) -> ChatCompletionRequest {
+        let prompt = if self.turn == 0 {
+            OPENING_PROMPT
+        } else {
+            FOLLOW_UPS[(self.turn - 1) % FOLLOW_UPS.len()]
+        };
+        self.messages.push(ChatMessage::user(prompt));
+        self.turn += 1;
+        ChatCompletionRequest::conversation_request(&self.model, self.messages.clone(), streaming)
+    }
+
+    pub fn record_reply(&mut self, content: String) {
+        self.messages.push(ChatMessage::assistant(&content));
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConversationTurnSample {
+    // 1-based conversation depth
+    pub turn: usize,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub time_to_first_token: Duration,
+    pub total_duration: Duration,
+}
+
+impl ConversationTurnSample {
+    // Without streaming the reply arrives at once, so there is no time per output token
+    pub fn goodput_sample(&self, streaming: bool) -> GoodputSample {
+        GoodputSample {
+            ttft: self.time_to_first_token,
+            tpot: (streaming && self.completion_tokens > 1).then(|| {
+                self.total_duration.saturating_sub(self.time_to_first_token) / (self.completion_tokens - 1)
+            }),
+            latency: self.total_duration,
+            tokens: Some(self.completion_tokens),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConversationDepthStats {
+    pub turn: usize,
+    pub samples: usize,
+    pub failures: usize,
+    pub mean_ttft: Duration,
+    pub p95_ttft: Duration,
+    pub mean_total_duration: Duration,
+    pub mean_prompt_tokens: u32,
+    pub mean_completion_tokens: u32,
+    // Prompt + completion tokens billed for all turns up to and including this one
+    pub mean_cumulative_tokens: u32,
+}
+
+#[derive(Debug)]
+pub struct ConversationStats {
+    pub model: String,
+    pub conversations: usize,
+    pub completed_conversations: usize,
+    pub depths: Vec<ConversationDepthStats>,
+    // TTFT (ms) against prompt size (thousands of tokens)
+    pub ttft_fit: Option<LinearFit>,
+    pub goodput: Option<GoodputStats>,
+}
+
+impl ConversationStats {
+    // TTFT at the first and deepest turns reached, plus its growth per 1k prompt tokens
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        let reached: Vec<&ConversationDepthStats> = self.depths.iter().filter(|d| d.samples > 0).collect();
+        let mut metrics = vec![
+            ("conversations", self.conversations as f64),
+            ("completed_conversations", self.completed_conversations as f64),
+        ];
+        if let (Some(first), Some(last)) = (reached.first(), reached.last()) {
+            metrics.push(("first_turn_ttft", ms(first.mean_ttft)));
+            metrics.push(("last_turn_ttft", ms(last.mean_ttft)));
+        }
+        if let Some(fit) = &self.ttft_fit {
+            metrics.push(("ttft_ms_per_1k_tokens", fit.slope));
+            metrics.push(("fit_r_squared", fit.r_squared));
+        }
+        metrics.extend(self.goodput.iter().flat_map(GoodputStats::history_metrics));
+        metrics
+    }
+}
+
+pub fn summarize(
+    model: &str,
+    turns: usize,
+    conversations: &[Vec<ConversationTurnSample>],
+    failed_turns: &[usize],
+    goodput: Option<GoodputStats>,
+) -> ConversationStats {
+    let depths = (1..=turns)
+        .map(|turn| {
+            let samples: Vec<&ConversationTurnSample> = conversations
+                .iter()
+                .filter_map(|c| c.iter().find(|s| s.turn == turn))
+                .collect();
+            let failures = failed_turns.iter().filter(|&&t| t == turn).count();
+            let n = samples.len();
+
+            if n == 0 {
+                return ConversationDepthStats {
+                    turn,
+                    samples: 0,
+                    failures,
+                    mean_ttft: Duration::from_secs(0),
+                    p95_ttft: Duration::from_secs(0),
+                    mean_total_duration: Duration::from_secs(0),
+                    mean_prompt_tokens: 0,
+                    mean_completion_tokens: 0,
+                    mean_cumulative_tokens: 0,
+                };
+            }
+
+            let mut ttfts: Vec<Duration> = samples.iter().map(|s| s.time_to_first_token).collect();
+            ttfts.sort();
+
+            let cumulative: u32 = conversations
+                .iter()
+                .filter(|c| c.iter().any(|s| s.turn == turn))
+                .map(|c| {
+                    c.iter()
+                        .filter(|s| s.turn <= turn)
+                        .map(|s| s.prompt_tokens + s.completion_tokens)
+                        .sum::<u32>()
+                })
+                .sum();
+
+            ConversationDepthStats {
+                turn,
+                samples: n,
+                failures,
+                mean_ttft: mean_duration(&ttfts),
+                p95_ttft: percentile(&ttfts, 95),
+                mean_total_duration: samples.iter().map(|s| s.total_duration).sum::<Duration>() / n as u32,
+                mean_prompt_tokens: samples.iter().map(|s| s.prompt_tokens).sum::<u32>() / n as u32,
+                mean_completion_tokens: samples.iter().map(|s| s.completion_tokens).sum::<u32>() / n as u32,
+                mean_cumulative_tokens: cumulative / n as u32,
+            }
+        })
+        .collect();
+
+    let fit_points: Vec<(f64, f64)> = conversations
+        .iter()
+        .flatten()
+        .map(|s| {
+            (
+                s.prompt_tokens as f64 / 1000.0,
+                s.time_to_first_token.as_secs_f64() * 1000.0,
+            )
+        })
+        .collect();
+
+    ConversationStats {
+        model: model.to_string(),
+        conversations: conversations.len(),
+        completed_conversations: conversations.iter().filter(|c| c.len() == turns).count(),
+        depths,
+        ttft_fit: linear_fit(&fit_points),
+        goodput,
+    }
+}
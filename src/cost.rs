@@ -0,0 +1,440 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+use crate::history::HistoryRecorder;
+use crate::models::{base_model_name, Usage};
+
+// Built-in list prices in USD per million tokens. They go stale; pass `--pricing FILE` to
+// override them or to price other models.
+const BUILTIN_PRICES: &[(&str, f64, f64, Option<f64>)] = &[
+    ("gpt-4o", 2.50, 10.00, Some(1.25)),
+    ("gpt-4o-mini", 0.15, 0.60, Some(0.075)),
+    ("gpt-4.1", 2.00, 8.00, Some(0.50)),
+    ("gpt-4.1-mini", 0.40, 1.60, Some(0.10)),
+    ("gpt-4.1-nano", 0.10, 0.40, Some(0.025)),
+    ("o3", 2.00, 8.00, Some(0.50)),
+    ("o4-mini", 1.10, 4.40, Some(0.275)),
+    ("claude-opus-4", 15.00, 75.00, Some(1.50)),
+    ("claude-sonnet-4", 3.00, 15.00, Some(0.30)),
+    ("claude-3-5-sonnet-20241022", 3.00, 15.00, Some(0.30)),
+    ("claude-3-5-haiku-20241022", 0.80, 4.00, Some(0.08)),
+    ("text-embedding-3-small", 0.02, 0.0, None),
+    ("text-embedding-3-large", 0.13, 0.0, None),
+];
+
+// Output tokens assumed by the budget for requests without `max_completion_tokens`
+const DEFAULT_COMPLETION_ESTIMATE: u32 = 4096;
+
+// USD per million tokens. Cached input tokens are billed at `input` when `cached` isn't set.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cached: Option<f64>,
+}
+
+impl ModelPrice {
+    pub fn cost(&self, tokens: &TokenUsage) -> f64 {
+        let uncached = tokens.input.saturating_sub(tokens.cached) as f64;
+        let cached = tokens.cached as f64 * self.cached.unwrap_or(self.input);
+        (uncached * self.input + cached + tokens.output as f64 * self.output) / 1_000_000.0
+    }
+}
+
+// The billable token counts of one response
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenUsage {
+    pub input: u32,
+    // Part of `input`
+    pub cached: u32,
+    pub output: u32,
+}
+
+impl From<&Usage> for TokenUsage {
+    fn from(usage: &Usage) -> Self {
+        Self {
+            input: usage.prompt_tokens,
+            cached: usage.cached_tokens().unwrap_or(0),
+            output: usage.completion_tokens.unwrap_or(0),
+        }
+    }
+}
+
+// Per-model prices: the built-in table, extended or overridden by a JSON file of the form
+// `{"model": {"input": 2.5, "output": 10.0, "cached": 1.25}}`.
+#[derive(Debug)]
+pub struct PricingTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl PricingTable {
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut prices: HashMap<String, ModelPrice> = BUILTIN_PRICES
+            .iter()
+            .map(|&(model, input, output, cached)| (model.to_string(), ModelPrice { input, output, cached }))
+            .collect();
+        if let Some(path) = path {
+            let text = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+            let file: HashMap<String, ModelPrice> =
+                serde_json::from_str(&text).with_context(|| format!("invalid pricing file {}", path.display()))?;
+            prices.extend(file);
+        }
+        Ok(Self { prices })
+    }
+
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        self.prices
+            .get(model)
+            .or_else(|| self.prices.get(base_model_name(model)))
+            .copied()
+    }
+}
+
+// Returned (inside anyhow) for requests refused because they could push spending past
+// `--max-cost`
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetExceeded {
+    pub budget: f64,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cost budget of ${:.4} reached", self.budget)
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+#[derive(Debug, Default, Clone)]
+pub struct ModelCost {
+    // Responses that reported usage
+    pub requests: usize,
+    // Requests that succeeded, with or without usage
+    pub successful_requests: usize,
+    pub input_tokens: u64,
+    pub cached_tokens: u64,
+    pub output_tokens: u64,
+    // None when the model has no price
+    pub cost: Option<f64>,
+    pub max_request_cost: f64,
+    // Successful responses without usage, which can't be priced
+    pub unmetered: usize,
+}
+
+impl ModelCost {
+    pub fn mean_request_cost(&self) -> Option<f64> {
+        self.cost.filter(|_| self.requests > 0).map(|cost| cost / self.requests as f64)
+    }
+
+    // Over successful requests, so failed requests that were still billed raise it
+    pub fn cost_per_1k_requests(&self) -> Option<f64> {
+        self.cost
+            .filter(|_| self.successful_requests > 0)
+            .map(|cost| cost / self.successful_requests as f64 * 1000.0)
+    }
+
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        let mut metrics = vec![
+            ("requests", self.requests as f64),
+            ("successful_requests", self.successful_requests as f64),
+            ("input_tokens", self.input_tokens as f64),
+            ("cached_tokens", self.cached_tokens as f64),
+            ("output_tokens", self.output_tokens as f64),
+        ];
+        if let (Some(cost), Some(per_1k)) = (self.cost, self.cost_per_1k_requests()) {
+            metrics.push(("cost_usd", cost));
+            metrics.push(("cost_per_1k_requests", per_1k));
+        }
+        metrics
+    }
+}
+
+#[derive(Debug, Default)]
+struct CostState {
+    models: BTreeMap<String, ModelCost>,
+    spent: f64,
+    // Estimated cost of requests in flight
+    reserved: f64,
+    refused: usize,
+    // Models without a price that were sent requests under a budget
+    unpriced: BTreeSet<String>,
+}
+
+// Adds up what every response cost, and with a budget, refuses requests that could take
+// spending past it. Shared by every client in a run.
+#[derive(Debug)]
+pub struct CostTracker {
+    pricing: PricingTable,
+    budget: Option<f64>,
+    state: Mutex<CostState>,
+}
+
+impl CostTracker {
+    pub fn new(pricing: PricingTable, budget: Option<f64>) -> Self {
+        Self {
+            pricing,
+            budget,
+            state: Mutex::new(CostState::default()),
+        }
+    }
+
+    pub fn has_budget(&self) -> bool {
+        self.budget.is_some()
+    }
+
+    // Called before sending a request with its prompt tokens and `max_completion_tokens`. The
+    // request is priced at that worst case, or at the model's most expensive response so far if
+    // that is higher, and the estimate is held until the request is settled or the reservation
+    // dropped.
+    pub fn reserve(
+        self: &Arc<Self>,
+        model: &str,
+        prompt_tokens: u32,
+        max_completion_tokens: Option<u32>,
+    ) -> Result<CostReservation> {
+        let mut state = self.state.lock().unwrap();
+        let estimate = match self.pricing.price(model) {
+            Some(price) => {
+                let worst_case = TokenUsage {
+                    input: prompt_tokens,
+                    cached: 0,
+                    output: max_completion_tokens.unwrap_or(DEFAULT_COMPLETION_ESTIMATE),
+                };
+                let seen = state.models.get(model).map_or(0.0, |m| m.max_request_cost);
+                price.cost(&worst_case).max(seen)
+            }
+            None => {
+                if self.budget.is_some() && state.unpriced.insert(model.to_string()) {
+                    warn!(
+                        "No price for {}: its requests are not counted against the cost budget; add it with --pricing",
+                        model
+                    );
+                }
+                0.0
+            }
+        };
+        if let Some(budget) = self.budget {
+            if state.spent + state.reserved + estimate > budget {
+                if state.refused == 0 {
+                    warn!(
+                        "Cost budget of ${:.4} reached (${:.4} spent); refusing further requests",
+                        budget, state.spent
+                    );
+                }
+                state.refused += 1;
+                return Err(BudgetExceeded { budget }.into());
+            }
+        }
+        state.reserved += estimate;
+        Ok(CostReservation {
+            tracker: Arc::clone(self),
+            estimate,
+        })
+    }
+
+    fn record(&self, model: &str, usage: Option<TokenUsage>, succeeded: bool) {
+        let mut state = self.state.lock().unwrap();
+        let price = self.pricing.price(model);
+        let entry = state.models.entry(model.to_string()).or_default();
+        if succeeded {
+            entry.successful_requests += 1;
+        }
+        let Some(usage) = usage else {
+            if succeeded {
+                entry.unmetered += 1;
+            }
+            return;
+        };
+        entry.requests += 1;
+        entry.input_tokens += usage.input as u64;
+        entry.cached_tokens += usage.cached as u64;
+        entry.output_tokens += usage.output as u64;
+        let cost = price.map(|p| p.cost(&usage));
+        if let Some(cost) = cost {
+            *entry.cost.get_or_insert(0.0) += cost;
+            entry.max_request_cost = entry.max_request_cost.max(cost);
+            state.spent += cost;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().models.is_empty()
+    }
+
+    // Stores each model's totals as the "cost" test of the run
+    pub fn record_history(&self, history: &HistoryRecorder) {
+        let state = self.state.lock().unwrap();
+        for (model, cost) in &state.models {
+            history.record_stats(model, "cost", &cost.history_metrics());
+        }
+    }
+
+    pub fn print_summary(&self) {
+        let state = self.state.lock().unwrap();
+        println!("\nCost Estimate");
+        println!("{}", "=".repeat(60));
+        println!(
+            "  {:<28} {:>9} {:>8} {:>10} {:>10} {:>10} {:>11} {:>11} {:>10}",
+            "Model", "Succeeded", "Metered", "Input", "Cached", "Output", "Cost", "Per req", "Per 1k"
+        );
+        let dollars = |cost: &ModelCost, value: Option<f64>, precision: usize| match value {
+            _ if cost.requests == 0 => "-".to_string(),
+            Some(v) => format!("${:.*}", precision, v),
+            None => "no price".to_string(),
+        };
+        let mut unmetered = 0;
+        for (model, cost) in &state.models {
+            println!(
+                "  {:<28} {:>9} {:>8} {:>10} {:>10} {:>10} {:>11} {:>11} {:>10}",
+                model,
+                cost.successful_requests,
+                cost.requests,
+                cost.input_tokens,
+                cost.cached_tokens,
+                cost.output_tokens,
+                dollars(cost, cost.cost, 4),
+                dollars(cost, cost.mean_request_cost(), 6),
+                dollars(cost, cost.cost_per_1k_requests(), 3)
+            );
+            unmetered += cost.unmetered;
+        }
+        println!("\nRun total: ${:.4}", state.spent);
+        if let Some(budget) = self.budget {
+            println!("Budget: ${:.4} ({:.1}% used)", budget, state.spent / budget * 100.0);
+            if state.refused > 0 {
+                println!("Requests refused by the budget: {}", state.refused);
+            }
+            if !state.unpriced.is_empty() {
+                let models: Vec<&str> = state.unpriced.iter().map(|m| m.as_str()).collect();
+                println!("⚠️  Not counted against the budget (no price): {}", models.join(", "));
+            }
+        }
+        if unmetered > 0 {
+            println!("Responses without usage (not priced): {}", unmetered);
+        }
+    }
+}
+
+pub fn parse_budget(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(budget) if budget > 0.0 && budget.is_finite() => Ok(budget),
+        _ => Err(format!("expected a positive amount of USD, got '{}'", value)),
+    }
+}
+
+// An admitted request's share of the budget. Settling records the actual cost; dropping it
+// (the request failed before a response) just releases the estimate.
+#[derive(Debug)]
+pub struct CostReservation {
+    tracker: Arc<CostTracker>,
+    estimate: f64,
+}
+
+impl CostReservation {
+    // `succeeded` is false for responses that were billed but failed, e.g. streams that
+    // ended before the first chunk
+    pub fn settle(self, model: &str, usage: Option<TokenUsage>, succeeded: bool) {
+        self.tracker.record(model, usage, succeeded);
+    }
+}
+
+impl Drop for CostReservation {
+    fn drop(&mut self) {
+        self.tracker.state.lock().unwrap().reserved -= self.estimate;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(budget: Option<f64>) -> Arc<CostTracker> {
+        Arc::new(CostTracker::new(PricingTable::load(None).unwrap(), budget))
+    }
+
+    fn usage(input: u32, cached: u32, output: u32) -> TokenUsage {
+        TokenUsage { input, cached, output }
+    }
+
+    #[test]
+    fn prices_cached_input_separately() {
+        let price = ModelPrice {
+            input: 2.0,
+            output: 10.0,
+            cached: Some(0.5),
+        };
+        // 600 uncached at $2, 400 cached at $0.50, 100 output at $10 per million
+        assert!((price.cost(&usage(1000, 400, 100)) - 0.0024).abs() < 1e-12);
+        let uncached = ModelPrice { cached: None, ..price };
+        assert!((uncached.cost(&usage(1000, 400, 100)) - 0.003).abs() < 1e-12);
+    }
+
+    #[test]
+    fn budgets_must_be_positive() {
+        assert_eq!(parse_budget("2.50"), Ok(2.5));
+        assert!(parse_budget("0").is_err());
+        assert!(parse_budget("-1").is_err());
+        assert!(parse_budget("inf").is_err());
+    }
+
+    #[test]
+    fn prefixed_models_use_the_base_price() {
+        let pricing = PricingTable::load(None).unwrap();
+        assert_eq!(pricing.price("openai/gpt-4o").unwrap().input, 2.50);
+        assert!(pricing.price("mock-llama").is_none());
+    }
+
+    #[test]
+    fn reserve_holds_the_worst_case_before_any_response() {
+        // gpt-4o: 1000 prompt tokens at $2.50 plus 1000 output tokens at $10 per million
+        let tracker = tracker(Some(0.045));
+        let reservations: Vec<CostReservation> =
+            (0..3).map(|_| tracker.reserve("gpt-4o", 1000, Some(1000)).unwrap()).collect();
+        let refused = tracker.reserve("gpt-4o", 1000, Some(1000)).unwrap_err();
+        assert!(refused.downcast_ref::<BudgetExceeded>().is_some());
+
+        // Releasing one reservation admits the next request
+        drop(reservations);
+        assert!(tracker.reserve("gpt-4o", 1000, Some(1000)).is_ok());
+    }
+
+    #[test]
+    fn unpriced_models_are_flagged_under_a_budget() {
+        let tracker = tracker(Some(0.01));
+        // Admitted, since there is no price to hold, but remembered for the warning
+        assert!(tracker.reserve("mock-llama", 1_000_000, None).is_ok());
+        assert!(tracker.reserve("mock-llama", 1_000_000, None).is_ok());
+        let state = tracker.state.lock().unwrap();
+        assert_eq!(state.unpriced.iter().collect::<Vec<_>>(), ["mock-llama"]);
+        assert_eq!(state.reserved, 0.0);
+    }
+
+    #[test]
+    fn reserve_assumes_the_default_completion_without_a_limit() {
+        // 4096 output tokens at $10 per million
+        let tracker = tracker(Some(0.04));
+        assert!(tracker.reserve("gpt-4o", 0, None).is_err());
+        assert!(tracker.reserve("gpt-4o", 0, Some(1000)).is_ok());
+    }
+
+    #[test]
+    fn settled_requests_are_spent_and_priced_per_successful_request() {
+        let tracker = tracker(None);
+        tracker.reserve("gpt-4o", 0, None).unwrap().settle("gpt-4o", Some(usage(1000, 0, 100)), true);
+        // Billed, but the stream failed
+        tracker.reserve("gpt-4o", 0, None).unwrap().settle("gpt-4o", Some(usage(1000, 0, 0)), false);
+        tracker.reserve("gpt-4o", 0, None).unwrap().settle("gpt-4o", None, true);
+
+        let state = tracker.state.lock().unwrap();
+        assert_eq!(state.reserved, 0.0);
+        assert!((state.spent - 0.006).abs() < 1e-12);
+        let cost = &state.models["gpt-4o"];
+        assert_eq!((cost.requests, cost.successful_requests, cost.unmetered), (2, 2, 1));
+        assert!((cost.cost_per_1k_requests().unwrap() - 3.0).abs() < 1e-9);
+    }
+}
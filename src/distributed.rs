@@ -0,0 +1,270 @@
+use anyhow::Result;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+use crate::hdr;
+use crate::server;
+
+// The most one `POST /run` may ask of an agent; requests are spawned up front, so this also
+// bounds its memory
+pub const MAX_SCENARIO_REQUESTS: usize = 100_000;
+pub const MAX_SCENARIO_CONCURRENCY: usize = 1_000;
+
+// The workload every agent runs: `requests` latency requests to one model at `concurrency`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub model: String,
+    pub requests: usize,
+    pub concurrency: usize,
+    pub streaming: bool,
+}
+
+impl Scenario {
+    pub fn validate(&self) -> Result<()> {
+        if self.requests == 0 || self.requests > MAX_SCENARIO_REQUESTS {
+            return Err(anyhow::anyhow!("requests must be between 1 and {}", MAX_SCENARIO_REQUESTS));
+        }
+        if self.concurrency == 0 || self.concurrency > MAX_SCENARIO_CONCURRENCY {
+            return Err(anyhow::anyhow!("concurrency must be between 1 and {}", MAX_SCENARIO_CONCURRENCY));
+        }
+        Ok(())
+    }
+}
+
+// Whether `request` carries `Authorization: Bearer <token>`. Compares every byte so the time
+// taken doesn't reveal how much of a guess was right.
+pub fn authorized(request: &server::Request, token: &str) -> bool {
+    let Some(given) = request.header("authorization").and_then(|v| v.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// Body of `POST /run`. Agents warm up, then wait until `start_at_ms` (Unix milliseconds) so
+// they all begin together.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunRequest {
+    pub scenario: Scenario,
+    pub start_at_ms: u64,
+}
+
+// Response to `POST /run`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgentResult {
+    pub succeeded: usize,
+    // `client::failure_label` -> requests
+    pub failures: BTreeMap<String, usize>,
+    pub wall_time_ms: f64,
+    // How late the agent started after `start_at_ms`, e.g. because warm-up ran long
+    pub start_lag_ms: f64,
+    // HdrHistogram interval log of the run, one interval per metric
+    pub hdr_log: String,
+}
+
+pub fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+#[derive(Debug, Clone)]
+pub struct CoordinatorConfig {
+    // host:port of each agent
+    pub agents: Vec<String>,
+    pub models: Vec<String>,
+    pub requests: usize,
+    pub concurrency: usize,
+    pub streaming: bool,
+    // Shared secret every agent was started with
+    pub token: String,
+    // Time agents get to receive the scenario and warm up before the synchronized start
+    pub start_delay: Duration,
+    // Where to write every agent's intervals as one interval log
+    pub hdr_log: Option<PathBuf>,
+}
+
+// Hands the scenario to every agent, waits for their results and reports the merged histograms.
+// Agents call the API with their own credentials; the coordinator never does.
+pub async fn run_coordinator(config: CoordinatorConfig) -> Result<()> {
+    let client = reqwest::Client::new();
+    Scenario {
+        model: String::new(),
+        requests: config.requests,
+        concurrency: config.concurrency,
+        streaming: config.streaming,
+    }
+    .validate()?;
+
+    // Every agent must be reachable before any of them is started
+    for agent in &config.agents {
+        let response = client
+            .get(format!("http://{}/health", agent))
+            .bearer_auth(&config.token)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Agent {} is unreachable: {}", agent, e))?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Agent {} is not ready: {}", agent, response.status()));
+        }
+    }
+
+    info!(
+        "Running {} models on {} agents: {} requests per agent at concurrency {}",
+        config.models.len(),
+        config.agents.len(),
+        config.requests,
+        config.concurrency
+    );
+
+    // Models run one after another, each with its own synchronized start
+    let mut intervals = Vec::new();
+    let mut summaries = Vec::new();
+    for model in &config.models {
+        let request = RunRequest {
+            scenario: Scenario {
+                model: model.clone(),
+                requests: config.requests,
+                concurrency: config.concurrency,
+                streaming: config.streaming,
+            },
+            start_at_ms: unix_millis(SystemTime::now() + config.start_delay),
+        };
+        info!("Starting {} on {} agents in {:?}", model, config.agents.len(), config.start_delay);
+
+        let runs = config.agents.iter().map(|agent| {
+            let client = &client;
+            let request = &request;
+            let token = &config.token;
+            async move {
+                let response = client
+                    .post(format!("http://{}/run", agent))
+                    .bearer_auth(token)
+                    .json(request)
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    return Err(anyhow::anyhow!("{} - {}", status, response.text().await.unwrap_or_default()));
+                }
+                Ok(response.json::<AgentResult>().await?)
+            }
+        });
+        let results = join_all(runs).await;
+
+        let mut agent_results = Vec::new();
+        for (agent, result) in config.agents.iter().zip(results) {
+            let result = result.map_err(|e| anyhow::anyhow!("Agent {} failed on {}: {}", agent, model, e))?;
+            intervals.extend(
+                hdr::read_log(result.hdr_log.as_bytes()).map_err(|e| anyhow::anyhow!("Agent {}: {}", agent, e))?,
+            );
+            agent_results.push((agent.as_str(), result));
+        }
+        summaries.push((model.as_str(), agent_results));
+    }
+
+    if let Some(path) = &config.hdr_log {
+        let start = intervals.iter().map(|i| i.start).min().unwrap_or_else(SystemTime::now);
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        hdr::write_log(&mut std::fs::File::create(path)?, start, &intervals)?;
+        info!("HdrHistogram log written to {}", path.display());
+    }
+
+    print_agent_results(&summaries);
+    let merged = hdr::merge(intervals)?;
+    hdr::print_merged(&format!("Merged Latency Across {} Agents", config.agents.len()), &merged);
+    Ok(())
+}
+
+fn print_agent_results(summaries: &[(&str, Vec<(&str, AgentResult)>)]) {
+    println!("\nDistributed Run Results");
+    println!("{}", "=".repeat(60));
+
+    for (model, rows) in summaries {
+        println!("\n🤖 Model: {}", model);
+        println!("─────────────────────────────");
+        println!(
+            "  {:<24} {:>9} {:>7} {:>10} {:>8} {:>10}  Failures",
+            "Agent", "Succeeded", "Failed", "Wall time", "Req/s", "Start lag"
+        );
+        let (mut succeeded, mut failed, mut wall_time) = (0, 0, 0.0f64);
+        for (agent, result) in rows {
+            let failures: usize = result.failures.values().sum();
+            let breakdown: Vec<String> = result.failures.iter().map(|(label, count)| format!("{}={}", label, count)).collect();
+            println!(
+                "  {:<24} {:>9} {:>7} {:>9.2}s {:>8.2} {:>8.0}ms  {}",
+                agent,
+                result.succeeded,
+                failures,
+                result.wall_time_ms / 1000.0,
+                requests_per_second(result.succeeded, result.wall_time_ms),
+                result.start_lag_ms,
+                if breakdown.is_empty() { "-".to_string() } else { breakdown.join(", ") }
+            );
+            succeeded += result.succeeded;
+            failed += failures;
+            wall_time = wall_time.max(result.wall_time_ms);
+        }
+        // Agents start together, so the aggregate rate is over the slowest agent's wall time
+        println!(
+            "  {:<24} {:>9} {:>7} {:>9.2}s {:>8.2}",
+            "Total",
+            succeeded,
+            failed,
+            wall_time / 1000.0,
+            requests_per_second(succeeded, wall_time)
+        );
+    }
+}
+
+fn requests_per_second(requests: usize, wall_time_ms: f64) -> f64 {
+    if wall_time_ms > 0.0 {
+        requests as f64 / (wall_time_ms / 1000.0)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(requests: usize, concurrency: usize) -> Scenario {
+        Scenario {
+            model: "gpt-4o".to_string(),
+            requests,
+            concurrency,
+            streaming: true,
+        }
+    }
+
+    fn request_with(authorization: Option<&str>) -> server::Request {
+        server::Request {
+            method: "POST".to_string(),
+            path: "/run".to_string(),
+            headers: authorization.map(|v| ("authorization".to_string(), v.to_string())).into_iter().collect(),
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn scenarios_must_be_bounded() {
+        assert!(scenario(100, 10).validate().is_ok());
+        assert!(scenario(0, 10).validate().is_err());
+        assert!(scenario(100, 0).validate().is_err());
+        assert!(scenario(MAX_SCENARIO_REQUESTS + 1, 10).validate().is_err());
+        assert!(scenario(100, MAX_SCENARIO_CONCURRENCY + 1).validate().is_err());
+    }
+
+    #[test]
+    fn only_the_shared_token_is_authorized() {
+        assert!(authorized(&request_with(Some("Bearer s3cret")), "s3cret"));
+        assert!(!authorized(&request_with(Some("Bearer s3cre")), "s3cret"));
+        assert!(!authorized(&request_with(Some("s3cret")), "s3cret"));
+        assert!(!authorized(&request_with(None), "s3cret"));
+    }
+}
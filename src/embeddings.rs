@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::metrics::{mean_duration, ms, percentile, weighted_mean, GoodputStats};
+
+#[derive(Debug, Clone)]
+pub struct EmbeddingSample {
+    pub batch_size: usize,
+    pub input_tokens: u32,
+    pub latency: Duration,
+    // Server-reported prompt tokens when available, otherwise the local count
+    pub tokens: u32,
+    // Length of every returned vector
+    pub dimensions: Vec<usize>,
+    // Returned vectors; differs from `batch_size` when the server dropped inputs
+    pub vectors: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmbeddingPoint {
+    pub batch_size: usize,
+    pub input_tokens: u32,
+    pub successful_requests: usize,
+    pub failed_requests: usize,
+    pub mean_latency: Duration,
+    pub p50_latency: Duration,
+    pub p95_latency: Duration,
+    pub p99_latency: Duration,
+    // Over the wall-clock time of the whole point, so concurrency counts
+    pub inputs_per_second: f64,
+    pub tokens_per_second: f64,
+}
+
+#[derive(Debug)]
+pub struct EmbeddingStats {
+    pub model: String,
+    pub points: Vec<EmbeddingPoint>,
+    // Vector length -> number of vectors returned with it; consistent when there is one key
+    pub dimensions: BTreeMap<usize, usize>,
+    // Responses that returned a different number of vectors than inputs sent
+    pub count_mismatches: usize,
+    pub goodput: Option<GoodputStats>,
+}
+
+impl EmbeddingStats {
+    pub fn dimensions_consistent(&self) -> bool {
+        self.dimensions.len() == 1
+    }
+
+    // Totals over every batch size and input length; throughput is the best point's
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("requests", self.points.iter().map(|p| p.successful_requests + p.failed_requests).sum::<usize>() as f64),
+            ("failed_requests", self.points.iter().map(|p| p.failed_requests).sum::<usize>() as f64),
+            ("mean_latency", weighted_mean(self.points.iter().map(|p| (ms(p.mean_latency), p.successful_requests)))),
+            ("peak_inputs_per_second", self.points.iter().map(|p| p.inputs_per_second).fold(0.0, f64::max)),
+            ("peak_tokens_per_second", self.points.iter().map(|p| p.tokens_per_second).fold(0.0, f64::max)),
+            ("count_mismatches", self.count_mismatches as f64),
+        ]
+        .into_iter()
+        .chain(self.goodput.iter().flat_map(GoodputStats::history_metrics))
+        .collect()
+    }
+}
+
+// `points` lists (batch size, input tokens, wall-clock time) for every case that ran.
+pub fn summarize(
+    model: &str,
+    points: &[(usize, u32, Duration)],
+    samples: &[EmbeddingSample],
+    failures: &[(usize, u32)],
+    goodput: Option<GoodputStats>,
+) -> EmbeddingStats {
+    let points = points
+        .iter()
+        .map(|&(batch_size, input_tokens, wall_time)| {
+            let point_samples: Vec<&EmbeddingSample> = samples
+                .iter()
+                .filter(|s| s.batch_size == batch_size && s.input_tokens == input_tokens)
+                .collect();
+            let mut latencies: Vec<Duration> = point_samples.iter().map(|s| s.latency).collect();
+            latencies.sort();
+            let n = latencies.len();
+            let wall_secs = wall_time.as_secs_f64();
+            let rate = |total: f64| if wall_secs > 0.0 { total / wall_secs } else { 0.0 };
+
+            EmbeddingPoint {
+                batch_size,
+                input_tokens,
+                successful_requests: n,
+                failed_requests: failures.iter().filter(|&&f| f == (batch_size, input_tokens)).count(),
+                mean_latency: mean_duration(&latencies),
+                p50_latency: percentile(&latencies, 50),
+                p95_latency: percentile(&latencies, 95),
+                p99_latency: percentile(&latencies, 99),
+                inputs_per_second: rate(point_samples.iter().map(|s| s.vectors).sum::<usize>() as f64),
+                tokens_per_second: rate(point_samples.iter().map(|s| s.tokens as u64).sum::<u64>() as f64),
+            }
+        })
+        .collect();
+
+    let mut dimensions = BTreeMap::new();
+    for dimension in samples.iter().flat_map(|s| s.dimensions.iter()) {
+        *dimensions.entry(*dimension).or_insert(0) += 1;
+    }
+
+    EmbeddingStats {
+        model: model.to_string(),
+        points,
+        dimensions,
+        count_mismatches: samples.iter().filter(|s| s.vectors != s.batch_size).count(),
+        goodput,
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error};
+
+use crate::metrics::MetricsCollector;
+use crate::server;
+
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 5.0, 10.0, 30.0, 60.0];
+const ITL_BUCKETS: &[f64] = &[0.002, 0.005, 0.01, 0.02, 0.03, 0.05, 0.075, 0.1, 0.2, 0.5];
+const TPS_BUCKETS: &[f64] = &[5.0, 10.0, 20.0, 30.0, 50.0, 75.0, 100.0, 150.0, 200.0, 300.0, 500.0];
+
+// Prometheus metrics for the `serve-metrics` prober, fed one `MetricsCollector` per model
+// per probe round.
+pub struct Exporter {
+    registry: Registry,
+    ttfb: HistogramVec,
+    ttfc: HistogramVec,
+    total: HistogramVec,
+    itl: HistogramVec,
+    tokens_per_second: HistogramVec,
+    succeeded: IntCounterVec,
+    failed: IntCounterVec,
+}
+
+fn histogram(registry: &Registry, name: &str, help: &str, buckets: &[f64]) -> Result<HistogramVec> {
+    let histogram = HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets.to_vec()), &["model"])?;
+    registry.register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+impl Exporter {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+        let succeeded = IntCounterVec::new(
+            Opts::new("sudo_bench_requests_succeeded_total", "Probe requests that completed"),
+            &["model"],
+        )?;
+        registry.register(Box::new(succeeded.clone()))?;
+        let failed = IntCounterVec::new(
+            Opts::new(
+                "sudo_bench_requests_failed_total",
+                "Probe requests that failed, by HTTP status, timeout_<phase> or error",
+            ),
+            &["model", "status"],
+        )?;
+        registry.register(Box::new(failed.clone()))?;
+
+        Ok(Self {
+            ttfb: histogram(&registry, "sudo_bench_ttfb_seconds", "Time to response headers", LATENCY_BUCKETS)?,
+            ttfc: histogram(&registry, "sudo_bench_ttfc_seconds", "Time to first streamed chunk", LATENCY_BUCKETS)?,
+            total: histogram(&registry, "sudo_bench_request_duration_seconds", "Total request time", LATENCY_BUCKETS)?,
+            itl: histogram(
+                &registry,
+                "sudo_bench_inter_token_latency_seconds",
+                "Mean gap between streamed chunks per request",
+                ITL_BUCKETS,
+            )?,
+            tokens_per_second: histogram(
+                &registry,
+                "sudo_bench_tokens_per_second",
+                "Generation speed after the first chunk per request",
+                TPS_BUCKETS,
+            )?,
+            registry,
+            succeeded,
+            failed,
+        })
+    }
+
+    pub fn record(&self, model: &str, collector: &MetricsCollector) {
+        for metric in collector.streaming_metrics() {
+            self.ttfb.with_label_values(&[model]).observe(metric.time_to_first_byte.as_secs_f64());
+            if let Some(ttfc) = metric.time_to_first_chunk {
+                self.ttfc.with_label_values(&[model]).observe(ttfc.as_secs_f64());
+            }
+            self.total.with_label_values(&[model]).observe(metric.total_duration.as_secs_f64());
+            if let Some(itl) = metric.inter_token_latency() {
+                self.itl.with_label_values(&[model]).observe(itl.as_secs_f64());
+            }
+            if let Some(tps) = metric.tokens_per_second() {
+                self.tokens_per_second.with_label_values(&[model]).observe(tps);
+            }
+            self.succeeded.with_label_values(&[model]).inc();
+        }
+        for status in collector.failures() {
+            self.failed.with_label_values(&[model, status]).inc();
+        }
+    }
+
+    fn render(&self) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+// A minimal HTTP/1.1 server: `GET /metrics` returns the text exposition format, anything
+// else is a 404. One request per connection.
+pub async fn serve(listener: TcpListener, exporter: Arc<Exporter>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let exporter = Arc::clone(&exporter);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_scrape(stream, &exporter).await {
+                        debug!("Metrics request from {} failed: {}", peer, e);
+                    }
+                });
+            }
+            Err(e) => error!("Failed to accept metrics connection: {}", e),
+        }
+    }
+}
+
+async fn handle_scrape(mut stream: TcpStream, exporter: &Exporter) -> Result<()> {
+    let request = server::read_request(&mut stream).await?;
+    if request.method == "GET" && request.path == "/metrics" {
+        server::write_response(&mut stream, "200 OK", TextEncoder::new().format_type(), &exporter.render()?).await
+    } else {
+        server::write_response(&mut stream, "404 Not Found", "text/plain", b"not found\n").await
+    }
+}
@@ -0,0 +1,282 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use hdrhistogram::serialization::interval_log::{IntervalLogIterator, IntervalLogWriterBuilder, LogEntry, Tag};
+use hdrhistogram::serialization::{Deserializer, V2DeflateSerializer};
+use hdrhistogram::Histogram;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::metrics::MetricsCollector;
+
+// Values are recorded in microseconds; the log's per-interval max column is in milliseconds
+const MAX_VALUE_DIVISOR: f64 = 1000.0;
+
+// One histogram covering a span of time, tagged `<model>/<metric>`
+pub struct Interval {
+    pub tag: String,
+    pub start: SystemTime,
+    pub duration: Duration,
+    pub histogram: Histogram<u64>,
+}
+
+impl Interval {
+    fn end(&self) -> SystemTime {
+        self.start + self.duration
+    }
+}
+
+// Interval tags can't contain commas or whitespace
+pub fn tag(model: &str, metric: &str) -> String {
+    let model: String = model.chars().map(|c| if c == ',' || c.is_whitespace() { '_' } else { c }).collect();
+    format!("{}/{}", model, metric)
+}
+
+// Metric names never contain '/', model names might
+fn split_tag(tag: &str) -> (&str, &str) {
+    tag.rsplit_once('/').unwrap_or((tag, ""))
+}
+
+// One interval per metric, from when the collector was created until now
+pub fn intervals(model: &str, collector: &MetricsCollector) -> Vec<Interval> {
+    let start = collector.started_at();
+    let duration = start.elapsed().unwrap_or_default();
+    collector
+        .histograms(model)
+        .into_iter()
+        .map(|(metric, histogram)| Interval {
+            tag: tag(model, metric),
+            start,
+            duration,
+            histogram,
+        })
+        .collect()
+}
+
+// Buffers one interval per model and metric for every test in a run and writes them as an
+// HdrHistogram interval log when the run ends.
+pub struct HdrLog {
+    path: PathBuf,
+    started: SystemTime,
+    intervals: Mutex<Vec<Interval>>,
+}
+
+impl HdrLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            started: SystemTime::now(),
+            intervals: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, model: &str, collector: &MetricsCollector) {
+        self.intervals.lock().unwrap().extend(intervals(model, collector));
+    }
+
+    pub fn write(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::File::create(&self.path)
+            .with_context(|| format!("failed to create {}", self.path.display()))?;
+        write_log(&mut file, self.started, &self.intervals.lock().unwrap())
+    }
+}
+
+// Writes intervals in start order with StartTime and BaseTime set to `start`, so interval
+// timestamps are seconds into the run
+pub fn write_log(writer: &mut impl Write, start: SystemTime, intervals: &[Interval]) -> Result<()> {
+    let mut sorted: Vec<&Interval> = intervals.iter().collect();
+    sorted.sort_by_key(|i| i.start);
+
+    let mut serializer = V2DeflateSerializer::new();
+    let mut log = IntervalLogWriterBuilder::new()
+        .add_comment("sudo-benchmarks latency histograms, values in microseconds, tagged <model>/<metric>")
+        .with_start_time(start)
+        .with_base_time(start)
+        .with_max_value_divisor(MAX_VALUE_DIVISOR)
+        .begin_log_with(writer, &mut serializer)?;
+    for interval in sorted {
+        let offset = interval.start.duration_since(start).unwrap_or_default();
+        log.write_histogram(&interval.histogram, offset, interval.duration, Tag::new(&interval.tag))?;
+    }
+    Ok(())
+}
+
+pub fn read_log(input: &[u8]) -> Result<Vec<Interval>> {
+    let mut base_time = None;
+    let mut intervals = Vec::new();
+    for entry in IntervalLogIterator::new(input) {
+        match entry.map_err(|e| anyhow::anyhow!("invalid interval log: {:?}", e))? {
+            LogEntry::BaseTime(time) => base_time = Some(time),
+            LogEntry::StartTime(_) => {}
+            LogEntry::Interval(interval) => {
+                let encoded = base64::engine::general_purpose::STANDARD.decode(interval.encoded_histogram())?;
+                let histogram: Histogram<u64> = Deserializer::new().deserialize(&mut encoded.as_slice())?;
+                // Without a BaseTime, timestamps are seconds since the epoch
+                let start = UNIX_EPOCH + base_time.unwrap_or_default() + interval.start_timestamp();
+                intervals.push(Interval {
+                    tag: interval.tag().map_or_else(String::new, |t| t.as_str().to_string()),
+                    start,
+                    duration: interval.duration(),
+                    histogram,
+                });
+            }
+        }
+    }
+    Ok(intervals)
+}
+
+// All intervals with one tag, added into a single histogram
+pub struct MergedHistogram {
+    pub intervals: usize,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub histogram: Histogram<u64>,
+}
+
+// Adds up the histograms per tag. Percentiles of the sum are exact over every recorded value,
+// unlike averaging each log's percentiles.
+pub fn merge(intervals: impl IntoIterator<Item = Interval>) -> Result<BTreeMap<String, MergedHistogram>> {
+    let mut merged: BTreeMap<String, MergedHistogram> = BTreeMap::new();
+    for interval in intervals {
+        let end = interval.end();
+        let entry = merged.entry(interval.tag).or_insert_with(|| MergedHistogram {
+            intervals: 0,
+            start: interval.start,
+            end,
+            histogram: Histogram::new(3).unwrap(),
+        });
+        entry.intervals += 1;
+        entry.start = entry.start.min(interval.start);
+        entry.end = entry.end.max(end);
+        entry.histogram.add(&interval.histogram)?;
+    }
+    Ok(merged)
+}
+
+// One interval per tag spanning everything that was merged into it
+pub fn merged_intervals(merged: BTreeMap<String, MergedHistogram>) -> Vec<Interval> {
+    merged
+        .into_iter()
+        .map(|(tag, m)| Interval {
+            tag,
+            start: m.start,
+            duration: m.end.duration_since(m.start).unwrap_or_default(),
+            histogram: m.histogram,
+        })
+        .collect()
+}
+
+fn ms(micros: u64) -> f64 {
+    micros as f64 / 1000.0
+}
+
+pub fn print_merged(title: &str, merged: &BTreeMap<String, MergedHistogram>) {
+    println!("\n{}", title);
+    println!("{}", "=".repeat(60));
+    if merged.is_empty() {
+        println!("No intervals found");
+        return;
+    }
+
+    let mut current_model = None;
+    for (tag, m) in merged {
+        let (model, metric) = split_tag(tag);
+        if current_model != Some(model) {
+            current_model = Some(model);
+            println!("\n🤖 Model: {}", model);
+            println!("─────────────────────────────");
+            println!(
+                "  {:<8} {:>9} {:>9} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+                "Metric", "Intervals", "Count", "Mean ms", "P50 ms", "P90 ms", "P99 ms", "P99.9 ms", "Max ms"
+            );
+        }
+        let h = &m.histogram;
+        println!(
+            "  {:<8} {:>9} {:>9} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2}",
+            metric,
+            m.intervals,
+            h.len(),
+            h.mean() / 1000.0,
+            ms(h.value_at_quantile(0.5)),
+            ms(h.value_at_quantile(0.9)),
+            ms(h.value_at_quantile(0.99)),
+            ms(h.value_at_quantile(0.999)),
+            ms(h.max())
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(tag: &str, start: SystemTime, seconds: u64, values: &[u64]) -> Interval {
+        let mut histogram = Histogram::new(3).unwrap();
+        for &value in values {
+            histogram.record(value).unwrap();
+        }
+        Interval {
+            tag: tag.to_string(),
+            start,
+            duration: Duration::from_secs(seconds),
+            histogram,
+        }
+    }
+
+    #[test]
+    fn log_round_trips_intervals() {
+        let run_start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let written = vec![
+            interval("openai/gpt-4o/ttfc", run_start + Duration::from_secs(2), 10, &[1_500, 2_500]),
+            interval("gpt-4o/latency", run_start, 5, &[10_000, 20_000, 30_000]),
+        ];
+        let mut log = Vec::new();
+        write_log(&mut log, run_start, &written).unwrap();
+
+        let read = read_log(&log).unwrap();
+        // Written in start order
+        assert_eq!(read.iter().map(|i| i.tag.as_str()).collect::<Vec<_>>(), ["gpt-4o/latency", "openai/gpt-4o/ttfc"]);
+        assert_eq!(read[0].start, run_start);
+        assert_eq!(read[1].start, run_start + Duration::from_secs(2));
+        assert_eq!(read[1].duration, Duration::from_secs(10));
+        assert_eq!(read[0].histogram, written[1].histogram);
+        assert_eq!(read[1].histogram, written[0].histogram);
+        assert_eq!(split_tag(&read[1].tag), ("openai/gpt-4o", "ttfc"));
+    }
+
+    #[test]
+    fn merge_adds_histograms_per_tag() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let merged = merge([
+            interval("gpt-4o/latency", start, 10, &[100, 200]),
+            interval("gpt-4o/latency", start + Duration::from_secs(5), 10, &[300, 400]),
+            interval("gpt-4o/ttfc", start, 10, &[50]),
+        ])
+        .unwrap();
+
+        let latency = &merged["gpt-4o/latency"];
+        assert_eq!(latency.intervals, 2);
+        assert_eq!(latency.start, start);
+        assert_eq!(latency.end, start + Duration::from_secs(15));
+        assert_eq!(latency.histogram.len(), 4);
+        assert_eq!(latency.histogram.max(), 400);
+        assert_eq!(merged["gpt-4o/ttfc"].histogram.len(), 1);
+
+        let intervals = merged_intervals(merged);
+        assert_eq!(intervals[0].duration, Duration::from_secs(15));
+    }
+
+    #[test]
+    fn tags_replace_commas_and_whitespace() {
+        assert_eq!(tag("my model,v2", "latency"), "my_model_v2/latency");
+    }
+}
@@ -0,0 +1,368 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+use crate::metrics::{ms, MetricsCollector};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at TEXT NOT NULL,
+    command TEXT NOT NULL,
+    args TEXT NOT NULL,
+    base_url TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS stats (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    model TEXT NOT NULL,
+    test TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    value REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS stats_model_metric ON stats (model, metric);
+CREATE TABLE IF NOT EXISTS samples (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    model TEXT NOT NULL,
+    test TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    value REAL NOT NULL
+);
+";
+
+// Run history in a local SQLite database. Stats are stored one row per (model, test,
+// metric) so any metric can be trended across runs; durations are in milliseconds.
+pub struct HistoryStore {
+    connection: Mutex<Connection>,
+}
+
+#[derive(Debug)]
+pub struct RunSummary {
+    pub id: i64,
+    pub started_at: String,
+    pub command: String,
+    pub args: String,
+    pub base_url: String,
+    pub models: usize,
+}
+
+#[derive(Debug)]
+pub struct StatRow {
+    pub model: String,
+    pub test: String,
+    pub metric: String,
+    pub value: f64,
+}
+
+#[derive(Debug)]
+pub struct TrendPoint {
+    pub run_id: i64,
+    pub started_at: String,
+    pub test: String,
+    pub value: f64,
+}
+
+impl HistoryStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let connection = Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    pub fn start_run(&self, command: &str, args: &str, base_url: &str) -> Result<i64> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO runs (started_at, command, args, base_url) VALUES (?1, ?2, ?3, ?4)",
+            params![chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true), command, args, base_url],
+        )?;
+        Ok(connection.last_insert_rowid())
+    }
+
+    pub fn record_stats(&self, run_id: i64, model: &str, test: &str, metrics: &[(&str, f64)]) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        {
+            let mut insert = transaction
+                .prepare("INSERT INTO stats (run_id, model, test, metric, value) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+            for (metric, value) in metrics {
+                insert.execute(params![run_id, model, test, metric, value])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    pub fn record_samples(&self, run_id: i64, model: &str, test: &str, collector: &MetricsCollector) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        {
+            let mut insert = transaction
+                .prepare("INSERT INTO samples (run_id, model, test, metric, value) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+            for (metric, value) in raw_samples(collector, model) {
+                insert.execute(params![run_id, model, test, metric, value])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    pub fn list_runs(&self, limit: usize) -> Result<Vec<RunSummary>> {
+        let connection = self.connection.lock().unwrap();
+        let mut query = connection.prepare(
+            "SELECT r.id, r.started_at, r.command, r.args, r.base_url, COUNT(DISTINCT s.model)
+             FROM runs r LEFT JOIN stats s ON s.run_id = r.id
+             GROUP BY r.id ORDER BY r.id DESC LIMIT ?1",
+        )?;
+        let runs = query
+            .query_map(params![limit as i64], run_summary)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(runs)
+    }
+
+    pub fn run(&self, id: i64) -> Result<Option<(RunSummary, Vec<StatRow>)>> {
+        let connection = self.connection.lock().unwrap();
+        let summary = connection
+            .query_row(
+                "SELECT r.id, r.started_at, r.command, r.args, r.base_url, COUNT(DISTINCT s.model)
+                 FROM runs r LEFT JOIN stats s ON s.run_id = r.id
+                 WHERE r.id = ?1 GROUP BY r.id",
+                params![id],
+                run_summary,
+            )
+            .optional()?;
+        let Some(summary) = summary else {
+            return Ok(None);
+        };
+
+        let mut query = connection
+            .prepare("SELECT model, test, metric, value FROM stats WHERE run_id = ?1 ORDER BY model, test, rowid")?;
+        let stats = query
+            .query_map(params![id], |row| {
+                Ok(StatRow {
+                    model: row.get(0)?,
+                    test: row.get(1)?,
+                    metric: row.get(2)?,
+                    value: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(Some((summary, stats)))
+    }
+
+    pub fn trend(&self, model: &str, metric: &str, test: Option<&str>) -> Result<Vec<TrendPoint>> {
+        let connection = self.connection.lock().unwrap();
+        let mut query = connection.prepare(
+            "SELECT r.id, r.started_at, s.test, s.value
+             FROM stats s JOIN runs r ON r.id = s.run_id
+             WHERE s.model = ?1 AND s.metric = ?2 AND (?3 IS NULL OR s.test = ?3)
+             ORDER BY r.id",
+        )?;
+        let points = query
+            .query_map(params![model, metric, test], |row| {
+                Ok(TrendPoint {
+                    run_id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    test: row.get(2)?,
+                    value: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(points)
+    }
+}
+
+// The run a benchmark command records into. The `runs` row is only written with the
+// first stats, so a command that measures nothing leaves no empty run behind.
+#[derive(Clone)]
+pub struct HistoryRecorder {
+    store: Arc<HistoryStore>,
+    run: Arc<Mutex<PendingRun>>,
+    // Also store the per-request values behind the stats
+    samples: bool,
+}
+
+struct PendingRun {
+    command: String,
+    args: String,
+    base_url: String,
+    id: Option<i64>,
+}
+
+impl HistoryRecorder {
+    pub fn new(store: Arc<HistoryStore>, command: &str, args: &str, base_url: &str, samples: bool) -> Self {
+        Self {
+            store,
+            run: Arc::new(Mutex::new(PendingRun {
+                command: command.to_string(),
+                args: args.to_string(),
+                base_url: base_url.to_string(),
+                id: None,
+            })),
+            samples,
+        }
+    }
+
+    fn run_id(&self) -> Result<i64> {
+        let mut run = self.run.lock().unwrap();
+        if let Some(id) = run.id {
+            return Ok(id);
+        }
+        let id = self.store.start_run(&run.command, &run.args, &run.base_url)?;
+        info!("Recording run {} in the history database", id);
+        run.id = Some(id);
+        Ok(id)
+    }
+
+    // Failing to write history never fails the benchmark
+    pub fn record(&self, model: &str, test: &str, metrics: &[(&str, f64)], collector: &MetricsCollector) {
+        self.record_stats(model, test, metrics);
+        if self.samples {
+            let result = self.run_id().and_then(|run_id| self.store.record_samples(run_id, model, test, collector));
+            if let Err(e) = result {
+                warn!("Failed to record {} samples for {}: {}", test, model, e);
+            }
+        }
+    }
+
+    // For benchmarks whose requests aren't kept in a `MetricsCollector`
+    pub fn record_stats(&self, model: &str, test: &str, metrics: &[(&str, f64)]) {
+        let result = self.run_id().and_then(|run_id| self.store.record_stats(run_id, model, test, metrics));
+        if let Err(e) = result {
+            warn!("Failed to record {} history for {}: {}", test, model, e);
+        }
+    }
+}
+
+fn run_summary(row: &rusqlite::Row) -> rusqlite::Result<RunSummary> {
+    Ok(RunSummary {
+        id: row.get(0)?,
+        started_at: row.get(1)?,
+        command: row.get(2)?,
+        args: row.get(3)?,
+        base_url: row.get(4)?,
+        models: row.get::<_, i64>(5)? as usize,
+    })
+}
+
+// Per-request values behind a model's stats
+fn raw_samples(collector: &MetricsCollector, model: &str) -> Vec<(&'static str, f64)> {
+    let mut samples = Vec::new();
+    for metric in collector.latency_metrics().iter().filter(|m| m.model == model) {
+        samples.push(("latency", ms(metric.total_duration)));
+        samples.push(("ttfb", ms(metric.time_to_first_byte)));
+    }
+    for metric in collector.streaming_metrics().iter().filter(|m| m.model == model) {
+        samples.push(("latency", ms(metric.total_duration)));
+        if let Some(ttfc) = metric.time_to_first_chunk {
+            samples.push(("ttfc", ms(ttfc)));
+        }
+        if let Some(tps) = metric.tokens_per_second() {
+            samples.push(("tokens_per_second", tps));
+        }
+    }
+    for metric in collector.throughput_metrics().iter().filter(|m| m.model == model && m.successful_requests > 0) {
+        samples.push(("tokens_per_second", metric.tokens_per_second));
+    }
+    samples
+}
+
+pub fn print_runs(runs: &[RunSummary]) {
+    println!("\nBenchmark History");
+    println!("{}", "=".repeat(60));
+    if runs.is_empty() {
+        println!("No runs recorded yet");
+        return;
+    }
+    println!("  {:>5}  {:<20}  {:<16}  {:>6}  Args", "Run", "Started", "Command", "Models");
+    for run in runs {
+        println!(
+            "  {:>5}  {:<20}  {:<16}  {:>6}  {}",
+            run.id,
+            run.started_at,
+            run.command,
+            run.models,
+            run.args
+        );
+    }
+}
+
+pub fn print_run(summary: &RunSummary, stats: &[StatRow]) {
+    println!("\nRun {}", summary.id);
+    println!("{}", "=".repeat(60));
+    println!("Started: {}", summary.started_at);
+    println!("Command: {}", summary.args);
+    println!("Base URL: {}", summary.base_url);
+
+    let mut current: Option<(&str, &str)> = None;
+    for stat in stats {
+        if current != Some((stat.model.as_str(), stat.test.as_str())) {
+            current = Some((stat.model.as_str(), stat.test.as_str()));
+            println!("\n🤖 Model: {} ({})", stat.model, stat.test);
+            println!("─────────────────────────────");
+        }
+        println!("  {:<24} {:>12.2}", stat.metric, stat.value);
+    }
+    if stats.is_empty() {
+        println!("\nNo stats recorded for this run");
+    }
+}
+
+pub fn print_trend(model: &str, metric: &str, points: &[TrendPoint]) {
+    println!("\nTrend: {} {}", model, metric);
+    println!("{}", "=".repeat(60));
+    if points.is_empty() {
+        println!("No recorded values");
+        return;
+    }
+
+    let max = points.iter().map(|p| p.value).fold(0.0, f64::max);
+    println!("  {:>5}  {:<20}  {:<18}  {:>10}  {:>8}", "Run", "Started", "Test", "Value", "Change");
+    // Changes are relative to the previous run of the same test
+    let mut previous: HashMap<&str, f64> = HashMap::new();
+    for point in points {
+        let change = previous
+            .get(point.test.as_str())
+            .filter(|&&p| p != 0.0)
+            .map_or_else(|| "-".to_string(), |p| format!("{:+.1}%", (point.value - p) / p * 100.0));
+        let bar = if max > 0.0 { "█".repeat((point.value / max * 30.0).round() as usize) } else { String::new() };
+        println!(
+            "  {:>5}  {:<20}  {:<18}  {:>10.2}  {:>8}  {}",
+            point.run_id,
+            point.started_at,
+            point.test,
+            point.value,
+            change,
+            bar
+        );
+        previous.insert(&point.test, point.value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_start_with_the_first_stats() {
+        let store = Arc::new(HistoryStore::open(Path::new(":memory:")).unwrap());
+        let recorder = HistoryRecorder::new(Arc::clone(&store), "tools", "tools -m gpt-4o", "http://localhost", false);
+        assert!(store.list_runs(10).unwrap().is_empty());
+
+        recorder.record_stats("gpt-4o", "tools", &[("requests", 10.0)]);
+        recorder.clone().record_stats("gpt-4o", "cost", &[("total_cost", 0.01)]);
+
+        let runs = store.list_runs(10).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].command, "tools");
+        assert_eq!(runs[0].models, 1);
+        let (_, stats) = store.run(runs[0].id).unwrap().unwrap();
+        assert_eq!(stats.len(), 2);
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::base_model_name;
+
+// Waiting requests check again at least this often, so tokens returned by finished requests
+// are picked up before the computed wait is over
+const RECHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+// Requests and tokens per minute; `None` leaves that dimension unlimited
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RateLimit {
+    pub requests_per_minute: Option<f64>,
+    pub tokens_per_minute: Option<f64>,
+}
+
+impl RateLimit {
+    fn is_unlimited(&self) -> bool {
+        self.requests_per_minute.is_none() && self.tokens_per_minute.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RateLimits {
+    pub global: RateLimit,
+    pub per_model: HashMap<String, RateLimit>,
+}
+
+// Refills continuously at the per-minute rate and holds at most one second's worth (at least
+// one unit), so load is spread out instead of bursting. A request larger than the bucket waits
+// for a full bucket and leaves it in debt, which later requests wait out.
+#[derive(Debug)]
+struct TokenBucket {
+    rate_per_second: f64,
+    capacity: f64,
+    level: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_minute: f64) -> Self {
+        let rate_per_second = per_minute / 60.0;
+        let capacity = rate_per_second.max(1.0);
+        Self {
+            rate_per_second,
+            capacity,
+            level: capacity,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.level = (self.level + elapsed * self.rate_per_second).min(self.capacity);
+        self.updated = now;
+    }
+
+    fn wait_for(&self, amount: f64) -> Duration {
+        let needed = amount.min(self.capacity) - self.level;
+        if needed <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(needed / self.rate_per_second)
+        }
+    }
+
+    fn adjust(&mut self, amount: f64) {
+        self.level = (self.level + amount).min(self.capacity);
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+}
+
+impl Buckets {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            requests: limit.requests_per_minute.map(TokenBucket::new),
+            tokens: limit.tokens_per_minute.map(TokenBucket::new),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WaitTotals {
+    pub requests: usize,
+    // Requests that had to wait at all
+    pub waited: usize,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    global: Buckets,
+    models: HashMap<String, Buckets>,
+    waits: BTreeMap<String, WaitTotals>,
+}
+
+// Client-side token buckets for requests/min and tokens/min, globally and per model.
+// Requests wait here before they are sent, so the wait never counts as request latency.
+// Tokens are estimated up front (prompt plus `max_completion_tokens`) and corrected once the
+// response's usage is known.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: RateLimits,
+    state: Mutex<LimiterState>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        let state = LimiterState {
+            global: Buckets::new(limits.global),
+            ..LimiterState::default()
+        };
+        Self {
+            limits,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.limits.global.is_unlimited() || self.limits.per_model.values().any(|l| !l.is_unlimited())
+    }
+
+    fn limit_for(&self, model: &str) -> RateLimit {
+        self.limits
+            .per_model
+            .get(model)
+            .or_else(|| self.limits.per_model.get(base_model_name(model)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // Waits until one request of about `tokens` tokens fits every bucket that applies to
+    // `model`, takes it from all of them, and returns how long that took
+    pub async fn acquire(&self, model: &str, tokens: u32) -> Duration {
+        if !self.is_enabled() {
+            return Duration::ZERO;
+        }
+        let start = Instant::now();
+        let mut slept = false;
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+                let LimiterState { global, models, waits } = &mut *state;
+                let model_buckets = models.entry(model.to_string()).or_insert_with(|| Buckets::new(self.limit_for(model)));
+                let mut buckets: Vec<(&mut TokenBucket, f64)> =
+                    [(&mut global.requests, 1.0), (&mut global.tokens, tokens as f64)]
+                        .into_iter()
+                        .chain([(&mut model_buckets.requests, 1.0), (&mut model_buckets.tokens, tokens as f64)])
+                        .filter_map(|(bucket, amount)| bucket.as_mut().map(|b| (b, amount)))
+                        .collect();
+
+                let mut wait = Duration::ZERO;
+                for (bucket, amount) in buckets.iter_mut() {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait_for(*amount));
+                }
+                if wait.is_zero() {
+                    for (bucket, amount) in buckets {
+                        bucket.adjust(-amount);
+                    }
+                    let waited = start.elapsed();
+                    let totals = waits.entry(model.to_string()).or_default();
+                    totals.requests += 1;
+                    if slept {
+                        totals.waited += 1;
+                    }
+                    totals.total += waited;
+                    totals.max = totals.max.max(waited);
+                    return waited;
+                }
+                wait
+            };
+            tokio::time::sleep(wait.min(RECHECK_INTERVAL)).await;
+            slept = true;
+        }
+    }
+
+    // Returns the difference between the estimated and actual tokens of a finished request
+    // to the token buckets (or takes the extra when the estimate was low)
+    pub fn settle_tokens(&self, model: &str, estimated: u32, actual: u32) {
+        if !self.is_enabled() || estimated == actual {
+            return;
+        }
+        let difference = estimated as f64 - actual as f64;
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let LimiterState { global, models, .. } = &mut *state;
+        let model_tokens = models.get_mut(model).and_then(|b| b.tokens.as_mut());
+        for bucket in [global.tokens.as_mut(), model_tokens].into_iter().flatten() {
+            bucket.refill(now);
+            bucket.adjust(difference);
+        }
+    }
+
+    pub fn print_summary(&self) {
+        let state = self.state.lock().unwrap();
+        println!("\nRate Limiter");
+        println!("{}", "=".repeat(60));
+        let describe = |limit: RateLimit| {
+            let mut parts = Vec::new();
+            if let Some(rpm) = limit.requests_per_minute {
+                parts.push(format!("{} req/min", rpm));
+            }
+            if let Some(tpm) = limit.tokens_per_minute {
+                parts.push(format!("{} tokens/min", tpm));
+            }
+            if parts.is_empty() { "unlimited".to_string() } else { parts.join(", ") }
+        };
+        println!("Global: {}", describe(self.limits.global));
+        println!(
+            "  {:<28} {:<28} {:>8} {:>7} {:>11} {:>11} {:>11}",
+            "Model", "Limit", "Requests", "Waited", "Total wait", "Mean wait", "Max wait"
+        );
+        for (model, totals) in &state.waits {
+            let mean = totals.total / totals.requests.max(1) as u32;
+            println!(
+                "  {:<28} {:<28} {:>8} {:>7} {:>10.2}s {:>9.1}ms {:>9.1}ms",
+                model,
+                if self.limit_for(model).is_unlimited() { "-".to_string() } else { describe(self.limit_for(model)) },
+                totals.requests,
+                totals.waited,
+                totals.total.as_secs_f64(),
+                mean.as_secs_f64() * 1000.0,
+                totals.max.as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
+
+pub fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err(format!("expected a positive number per minute, got '{}'", value)),
+    }
+}
+
+// Parses `MODEL=N` pairs given on the command line
+pub fn parse_model_rate(value: &str) -> Result<(String, f64), String> {
+    let (model, rate) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODEL=RATE, got '{}'", value))?;
+    if model.is_empty() {
+        return Err(format!("expected MODEL=RATE, got '{}'", value));
+    }
+    Ok((model.to_string(), parse_rate(rate)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket_at(per_minute: f64, level: f64, updated: Instant) -> TokenBucket {
+        TokenBucket {
+            level,
+            updated,
+            ..TokenBucket::new(per_minute)
+        }
+    }
+
+    #[test]
+    fn refill_adds_the_elapsed_rate_up_to_capacity() {
+        let start = Instant::now();
+        let mut bucket = bucket_at(120.0, 0.0, start);
+        bucket.refill(start + Duration::from_millis(250));
+        assert!((bucket.level - 0.5).abs() < 1e-9);
+        bucket.refill(start + Duration::from_secs(10));
+        assert_eq!(bucket.level, bucket.capacity);
+    }
+
+    #[test]
+    fn oversized_request_leaves_debt_for_the_next() {
+        let start = Instant::now();
+        // 10 tokens/s, holding at most 10
+        let mut bucket = bucket_at(600.0, 10.0, start);
+        assert_eq!(bucket.wait_for(50.0), Duration::ZERO);
+        bucket.adjust(-50.0);
+        assert_eq!(bucket.level, -40.0);
+        // The next request waits out the debt plus its own share
+        assert_eq!(bucket.wait_for(10.0), Duration::from_secs(5));
+        bucket.refill(start + Duration::from_secs(5));
+        assert_eq!(bucket.wait_for(10.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn settle_tokens_returns_an_overestimate() {
+        let limiter = RateLimiter::new(RateLimits {
+            global: RateLimit {
+                tokens_per_minute: Some(600.0),
+                ..RateLimit::default()
+            },
+            ..RateLimits::default()
+        });
+        // Stamped in the future so settling refills nothing, however long the test takes
+        let later = Instant::now() + Duration::from_secs(3600);
+        {
+            let mut state = limiter.state.lock().unwrap();
+            // A 100-token request was taken from full buckets of 10
+            state.global.tokens = Some(bucket_at(600.0, -90.0, later));
+            state.models.insert(
+                "gpt-4o".to_string(),
+                Buckets {
+                    requests: None,
+                    tokens: Some(bucket_at(600.0, -90.0, later)),
+                },
+            );
+        }
+        limiter.settle_tokens("gpt-4o", 100, 20);
+
+        let state = limiter.state.lock().unwrap();
+        assert_eq!(state.global.tokens.as_ref().unwrap().level, -10.0);
+        assert_eq!(state.models["gpt-4o"].tokens.as_ref().unwrap().level, -10.0);
+    }
+
+    #[test]
+    fn per_model_limits_ignore_the_provider_prefix() {
+        let limiter = RateLimiter::new(RateLimits {
+            per_model: HashMap::from([(
+                "gpt-4o".to_string(),
+                RateLimit {
+                    requests_per_minute: Some(60.0),
+                    ..RateLimit::default()
+                },
+            )]),
+            ..RateLimits::default()
+        });
+        assert_eq!(limiter.limit_for("openai/gpt-4o").requests_per_minute, Some(60.0));
+        assert_eq!(limiter.limit_for("gpt-4o").requests_per_minute, Some(60.0));
+        assert!(limiter.limit_for("gpt-4o-mini").is_unlimited());
+    }
+
+    #[test]
+    fn parses_model_rates() {
+        assert_eq!(parse_model_rate("gpt-4o=30"), Ok(("gpt-4o".to_string(), 30.0)));
+        assert!(parse_model_rate("=30").is_err());
+        assert!(parse_model_rate("gpt-4o=0").is_err());
+        assert!(parse_model_rate("gpt-4o").is_err());
+    }
+}
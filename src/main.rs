@@ -1,15 +1,53 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use dotenvy::dotenv;
 use std::env;
-use tracing::info;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
 
+mod audit;
 mod benchmarks;
+mod cache;
 mod client;
+mod connection;
+mod conversation;
+mod cost;
+mod distributed;
+mod embeddings;
+mod exporter;
+mod hdr;
+mod history;
+mod limiter;
 mod models;
+mod monitor;
 mod metrics;
+mod phases;
+mod report;
+mod schema;
+mod server;
+mod structured;
+mod sweep;
+mod telemetry;
+mod tokenizer;
+mod tools;
+mod vision;
 
-use benchmarks::{BenchmarkConfig, BenchmarkRunner};
+use benchmarks::{
+    AgentConfig, BenchmarkConfig, BenchmarkRunner, CacheConfig, ConnectionConfig, ConversationConfig, DecodeSweepConfig, EmbeddingConfig,
+    MonitorConfig, PrefillSweepConfig, ProtocolConfig, ReportConfig, ServeMetricsConfig, StructuredConfig, ToolConfig, VisionConfig,
+};
+use client::{ClientConfig, ConnectionMode, HttpVersion, Timeouts};
+use cost::{CostTracker, PricingTable};
+use distributed::CoordinatorConfig;
+use hdr::HdrLog;
+use metrics::GoodputSlo;
+use history::{HistoryRecorder, HistoryStore};
+use limiter::{RateLimit, RateLimiter, RateLimits};
+use monitor::SloConfig;
+use tokenizer::TokenizerRegistry;
 
 #[derive(Parser)]
 #[command(name = "sudo-benchmarks")]
@@ -17,6 +55,81 @@ use benchmarks::{BenchmarkConfig, BenchmarkRunner};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Tokenizer for a model when the server omits usage, as MODEL=SPEC where SPEC is a
+    /// tiktoken encoding (e.g. o200k_base) or a path to a tokenizer.json (repeatable)
+    #[arg(long, global = true, value_parser = tokenizer::parse_tokenizer_override)]
+    tokenizer: Vec<(String, PathBuf)>,
+    /// `reasoning_effort` sent with every chat request (e.g. low, medium, high)
+    #[arg(long, global = true)]
+    reasoning_effort: Option<String>,
+    /// Time DNS, TCP connect, TLS handshake, request write, server wait and body download
+    /// for every request
+    #[arg(long, global = true)]
+    phase_timing: bool,
+    /// How requests get connections: one shared pool (warm), a client per concurrent worker
+    /// (per-worker), or a new client and connection for every request (cold)
+    #[arg(long, global = true, value_enum, default_value = "warm")]
+    connection_mode: ConnectionMode,
+    /// HTTP version: http1 (HTTP/1.1 only), auto (negotiated via ALPN) or http2 (HTTP/2 only)
+    #[arg(long, global = true, value_enum, default_value = "http1")]
+    http_version: HttpVersion,
+    /// Seconds allowed to establish a connection
+    #[arg(long, global = true, value_parser = parse_seconds)]
+    connect_timeout: Option<Duration>,
+    /// Seconds allowed from sending a request to its response headers
+    #[arg(long, global = true, value_parser = parse_seconds)]
+    ttfb_timeout: Option<Duration>,
+    /// Longest gap in seconds allowed between streamed chunks
+    #[arg(long, global = true, value_parser = parse_seconds)]
+    idle_timeout: Option<Duration>,
+    /// Seconds allowed for a whole request, including the streamed body
+    #[arg(long, global = true, value_parser = parse_seconds, default_value = "120")]
+    total_timeout: Duration,
+    /// Export a span per request to this OTLP/HTTP traces endpoint
+    /// (e.g. http://localhost:4318/v1/traces)
+    #[arg(long, global = true)]
+    otlp_endpoint: Option<String>,
+    /// SQLite database every benchmark run is recorded in
+    #[arg(long, global = true, default_value = "results/history.db")]
+    history_db: PathBuf,
+    /// Don't record this run in the history database
+    #[arg(long, global = true)]
+    no_history: bool,
+    /// Also record every request's latency, TTFC and tokens/s, not just the per-model stats
+    #[arg(long, global = true)]
+    history_samples: bool,
+    /// Write microsecond HdrHistogram interval logs of each model's latency, TTFB, TTFC and
+    /// inter-token latency to this file
+    #[arg(long, global = true)]
+    hdr_log: Option<PathBuf>,
+    /// Goodput SLO: seconds allowed to the first token (the whole response when not streaming)
+    #[arg(long, global = true, value_parser = parse_seconds)]
+    slo_ttft: Option<Duration>,
+    /// Goodput SLO: seconds allowed per output token after the first (streaming only)
+    #[arg(long, global = true, value_parser = parse_seconds)]
+    slo_tpot: Option<Duration>,
+    /// Goodput SLO: seconds allowed for the whole request
+    #[arg(long, global = true, value_parser = parse_seconds)]
+    slo_latency: Option<Duration>,
+    /// JSON file of per-model prices in USD per million tokens, added to the built-in table:
+    /// {"model": {"input": 2.5, "output": 10.0, "cached": 1.25}}
+    #[arg(long, global = true)]
+    pricing: Option<PathBuf>,
+    /// Stop sending requests before the estimated spend of the run would exceed this many USD
+    #[arg(long, global = true, value_parser = cost::parse_budget)]
+    max_cost: Option<f64>,
+    /// Client-side limit on requests per minute across all models
+    #[arg(long, global = true, value_parser = limiter::parse_rate)]
+    rpm: Option<f64>,
+    /// Client-side limit on tokens (prompt plus max completion) per minute across all models
+    #[arg(long, global = true, value_parser = limiter::parse_rate)]
+    tpm: Option<f64>,
+    /// Requests per minute for one model, as MODEL=N (repeatable)
+    #[arg(long, global = true, value_parser = limiter::parse_model_rate)]
+    model_rpm: Vec<(String, f64)>,
+    /// Tokens per minute for one model, as MODEL=N (repeatable)
+    #[arg(long, global = true, value_parser = limiter::parse_model_rate)]
+    model_tpm: Vec<(String, f64)>,
 }
 
 #[derive(Subcommand)]
@@ -45,6 +158,290 @@ enum Commands {
         #[arg(short, long, value_delimiter = ',')]
         model: Vec<String>,
     },
+    /// Audit server-reported usage against local token counts
+    AuditUsage {
+        /// Model to audit (if not specified, audits all models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Percentage difference above which a count is flagged
+        #[arg(long, default_value = "5")]
+        tolerance: f64,
+    },
+    /// Sweep prompt length and report TTFT vs input tokens
+    PrefillSweep {
+        /// Model to benchmark (if not specified, benchmarks a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Input lengths in tokens
+        #[arg(long, value_delimiter = ',', default_value = "128,512,2048,8192,32768,131072")]
+        lengths: Vec<u32>,
+        /// Number of requests per input length
+        #[arg(short, long, default_value = "3")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+        /// Context window for a model the models endpoint doesn't describe, as MODEL=TOKENS (repeatable)
+        #[arg(long = "context-window", value_parser = parse_context_window)]
+        context_windows: Vec<(String, u32)>,
+    },
+    /// Sweep max_completion_tokens and report decode speed vs output length
+    DecodeSweep {
+        /// Model to benchmark (if not specified, benchmarks a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Output lengths (max_completion_tokens) to test
+        #[arg(long, value_delimiter = ',', default_value = "64,256,1024,4096")]
+        output_lengths: Vec<u32>,
+        /// Number of requests per output length
+        #[arg(short, long, default_value = "3")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Run multi-turn conversations and report how TTFT and tokens grow with depth
+    Conversation {
+        /// Model to benchmark (if not specified, benchmarks a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Number of turns per conversation
+        #[arg(short, long, default_value = "8")]
+        turns: usize,
+        /// Number of conversations per model
+        #[arg(long, default_value = "3")]
+        conversations: usize,
+        /// Number of conversations running concurrently
+        #[arg(short, long, default_value = "3")]
+        concurrency: usize,
+        /// Disable streaming (conversations default to streaming)
+        #[arg(long = "streaming-off")]
+        streaming_off: bool,
+    },
+    /// Measure prompt caching: cold vs warm TTFT and cached-token ratio
+    Cache {
+        /// Model to benchmark (if not specified, benchmarks a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Length of the shared prompt prefix in tokens
+        #[arg(long, default_value = "4096")]
+        prefix_tokens: u32,
+        /// Number of rounds, each with a fresh prefix
+        #[arg(long, default_value = "3")]
+        rounds: usize,
+        /// Warm requests per round after the cold one
+        #[arg(long, default_value = "4")]
+        warm_requests: usize,
+        /// Milliseconds to wait after each cold request before sending warm ones
+        #[arg(long, default_value = "1000")]
+        settle_ms: u64,
+    },
+    /// Measure tool-calling latency, argument validity and tool-result round trips
+    Tools {
+        /// Model to benchmark (if not specified, benchmarks a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Number of tool-calling requests per model
+        #[arg(short, long, default_value = "10")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+        /// Value sent as `tool_choice` (auto, required or none)
+        #[arg(long, default_value = "auto")]
+        tool_choice: String,
+    },
+    /// Measure structured output (response_format) latency and schema conformance
+    Structured {
+        /// Model to benchmark (if not specified, benchmarks a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Number of requests per model
+        #[arg(short, long, default_value = "10")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+        /// Use `json_object` mode (schema described in the prompt) instead of `json_schema`
+        #[arg(long)]
+        json_object: bool,
+    },
+    /// Benchmark the embeddings endpoint across batch sizes and input lengths
+    Embeddings {
+        /// Embedding models to benchmark (defaults to listed models with "embed" in the name)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Inputs per request
+        #[arg(long, value_delimiter = ',', default_value = "1,8,32")]
+        batch_sizes: Vec<usize>,
+        /// Tokens per input
+        #[arg(long, value_delimiter = ',', default_value = "16,128,512")]
+        input_tokens: Vec<u32>,
+        /// Requests per batch size and input length
+        #[arg(short, long, default_value = "5")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Measure TTFT against image count and resolution for vision-capable models
+    Vision {
+        /// Vision-capable models to benchmark (the models endpoint doesn't say which accept images)
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        model: Vec<String>,
+        /// Local image files (PNG, JPEG, GIF, WebP); overrides --resolutions
+        #[arg(long, value_delimiter = ',')]
+        image: Vec<PathBuf>,
+        /// Side lengths in pixels of the synthetic square images (at most 8192)
+        #[arg(long, value_delimiter = ',', value_parser = vision::parse_resolution, default_value = "256,512,1024,2048")]
+        resolutions: Vec<u32>,
+        /// Images per request
+        #[arg(long, value_delimiter = ',', default_value = "1,2,4")]
+        image_counts: Vec<usize>,
+        /// Requests per image case
+        #[arg(short, long, default_value = "3")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Compare TTFT and total time across connection modes to quantify connection setup cost
+    Connections {
+        /// Model to benchmark (if not specified, benchmarks a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Connection modes to compare
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "warm,per-worker,cold")]
+        modes: Vec<ConnectionMode>,
+        /// Requests per mode
+        #[arg(short, long, default_value = "20")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "4")]
+        concurrency: usize,
+    },
+    /// Compare HTTP/1.1 and HTTP/2 over the same streaming workload
+    Protocols {
+        /// Model to benchmark (if not specified, benchmarks a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// HTTP versions to compare
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "http1,http2")]
+        versions: Vec<HttpVersion>,
+        /// Requests per HTTP version
+        #[arg(short, long, default_value = "200")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "100")]
+        concurrency: usize,
+    },
+    /// Probe models periodically and expose the results as Prometheus metrics
+    ServeMetrics {
+        /// Address to serve `/metrics` on
+        #[arg(long, default_value = "0.0.0.0:9464")]
+        listen: SocketAddr,
+        /// Seconds between probe rounds
+        #[arg(long, value_parser = parse_positive_seconds, default_value = "60")]
+        interval: Duration,
+        /// Models to probe (if not specified, probes a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Streaming requests per model per round
+        #[arg(short, long, default_value = "5")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Probe models periodically, evaluate SLOs and alert a webhook on breach and recovery
+    Monitor {
+        /// Seconds between probe rounds
+        #[arg(long, value_parser = parse_positive_seconds, default_value = "60")]
+        interval: Duration,
+        /// Models to probe (if not specified, probes a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Streaming requests per model per round
+        #[arg(short, long, default_value = "3")]
+        requests: usize,
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+        /// Seconds of probe history the SLOs are evaluated over
+        #[arg(long, value_parser = parse_positive_seconds, default_value = "3600")]
+        window: Duration,
+        /// SLO: p95 time to first chunk must stay below this many seconds
+        #[arg(long, value_parser = parse_seconds, default_value = "1.5")]
+        slo_p95_ttfc: Duration,
+        /// SLO: success rate must stay above this percentage
+        #[arg(long, default_value = "99")]
+        slo_success_rate: f64,
+        /// URL to POST a JSON alert to on breach and recovery
+        #[arg(long)]
+        webhook: Option<String>,
+    },
+    /// Run streaming latency and throughput tests and write a self-contained HTML report
+    Report {
+        /// File to write the report to
+        #[arg(long)]
+        html: PathBuf,
+        /// Models to include (if not specified, reports on a subset of models)
+        #[arg(short, long, value_delimiter = ',')]
+        model: Vec<String>,
+        /// Streaming latency requests per model
+        #[arg(short, long, default_value = "50")]
+        requests: usize,
+        /// Number of concurrent requests (also the throughput test's request count)
+        #[arg(short, long, default_value = "5")]
+        concurrency: usize,
+    },
+    /// Wait for a coordinator and run the scenarios it sends, using this machine's API credentials
+    Agent {
+        /// Address to accept coordinator requests on
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: SocketAddr,
+        /// Shared secret coordinators must present
+        #[arg(long, required = true)]
+        token: String,
+    },
+    /// Run a scenario on several agents at once and merge their histograms into one report
+    Coordinator {
+        /// Agent addresses as host:port
+        #[arg(long, value_delimiter = ',', required = true)]
+        agents: Vec<String>,
+        /// Models to benchmark, one synchronized run each
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        model: Vec<String>,
+        /// Requests per model per agent
+        #[arg(short, long, default_value = "100")]
+        requests: usize,
+        /// Concurrent requests per agent
+        #[arg(short, long, default_value = "10")]
+        concurrency: usize,
+        /// Disable streaming (latency defaults to streaming)
+        #[arg(long = "streaming-off")]
+        streaming_off: bool,
+        /// Seconds between handing out a run and its synchronized start; covers agent warm-up
+        #[arg(long, value_parser = parse_seconds, default_value = "5")]
+        start_delay: Duration,
+        /// Shared secret the agents were started with
+        #[arg(long, required = true)]
+        token: String,
+    },
+    /// Combine HdrHistogram interval logs from several runs or machines into aggregate percentiles
+    Merge {
+        /// Interval logs written with --hdr-log
+        #[arg(required = true)]
+        logs: Vec<PathBuf>,
+        /// Also write the merged histograms as an interval log, one interval per model and metric
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Browse recorded runs and per-model trends
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
     /// List all supported models
     Models,
     /// Run comprehensive benchmark suite
@@ -58,20 +455,176 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// List recent runs
+    List {
+        /// Number of runs to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    /// Show the per-model stats recorded for a run
+    Show {
+        /// Run id, as shown by `history list`
+        run: i64,
+    },
+    /// Show how a model's metric changed across runs
+    Trend {
+        #[arg(short, long)]
+        model: String,
+        /// Recorded metric, e.g. p95_ttfc, p95_latency, mean_ttfb, tokens_per_second, success_rate
+        #[arg(long, default_value = "p95_ttfc")]
+        metric: String,
+        /// Only runs of this test (latency, streaming_latency or throughput)
+        #[arg(long)]
+        test: Option<String>,
+    },
+}
+
+impl Cli {
+    // Goodput is only reported when at least one objective is set
+    fn goodput_slo(&self) -> Option<GoodputSlo> {
+        let has_slo = self.slo_ttft.is_some() || self.slo_tpot.is_some() || self.slo_latency.is_some();
+        has_slo.then_some(GoodputSlo {
+            max_ttft: self.slo_ttft,
+            max_tpot: self.slo_tpot,
+            max_latency: self.slo_latency,
+        })
+    }
+}
+
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid number of seconds '{}'", value))?;
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("invalid number of seconds '{}'", value))
+}
+
+// For periods that drive a timer or a window, where zero is meaningless
+fn parse_positive_seconds(value: &str) -> Result<Duration, String> {
+    match parse_seconds(value)? {
+        duration if duration.is_zero() => Err(format!("expected more than 0 seconds, got '{}'", value)),
+        duration => Ok(duration),
+    }
+}
+
+fn parse_context_window(value: &str) -> Result<(String, u32), String> {
+    let (model, tokens) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODEL=TOKENS, got '{}'", value))?;
+    let tokens = tokens
+        .parse()
+        .map_err(|_| format!("invalid token count in '{}'", value))?;
+    Ok((model.to_string(), tokens))
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+
+    // Every benchmark reports goodput; the usage audit and the non-benchmark commands don't
+    if cli.goodput_slo().is_some()
+        && matches!(
+            cli.command,
+            Commands::AuditUsage { .. }
+                | Commands::ServeMetrics { .. }
+                | Commands::Monitor { .. }
+                | Commands::Agent { .. }
+                | Commands::Coordinator { .. }
+                | Commands::Merge { .. }
+                | Commands::History { .. }
+                | Commands::Models
+        )
+    {
+        return Err(anyhow::anyhow!(
+            "--slo-ttft, --slo-tpot and --slo-latency only apply to benchmark commands"
+        ));
+    }
+
     // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter("info")
-        .init();
+    let tracer_provider = telemetry::init(cli.otlp_endpoint.clone()).await?;
+
+    // Flush buffered request spans whether or not the run succeeded; a failed run's spans are
+    // the ones most worth keeping
+    let result = run(cli, &matches).await;
+    let shutdown = telemetry::shutdown(tracer_provider).await;
+    match result {
+        Ok(()) => shutdown,
+        Err(e) => {
+            if let Err(shutdown_error) = shutdown {
+                warn!("Failed to flush traces: {}", shutdown_error);
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn run(cli: Cli, matches: &ArgMatches) -> Result<()> {
+    // History, merging and coordinating are local, without the API
+    match &cli.command {
+        Commands::History { command } => {
+            let store = HistoryStore::open(&cli.history_db)?;
+            match command {
+                HistoryCommand::List { limit } => history::print_runs(&store.list_runs(*limit)?),
+                HistoryCommand::Show { run } => {
+                    let (summary, stats) = store
+                        .run(*run)?
+                        .ok_or_else(|| anyhow::anyhow!("No run {} in {}", run, cli.history_db.display()))?;
+                    history::print_run(&summary, &stats);
+                }
+                HistoryCommand::Trend { model, metric, test } => {
+                    history::print_trend(model, metric, &store.trend(model, metric, test.as_deref())?);
+                }
+            }
+            return Ok(());
+        }
+        Commands::Merge { logs, output } => {
+            let mut intervals = Vec::new();
+            for path in logs {
+                let input = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+                intervals.extend(hdr::read_log(&input).with_context(|| format!("failed to parse {}", path.display()))?);
+            }
+            let merged = hdr::merge(intervals)?;
+            hdr::print_merged(&format!("Merged HdrHistogram Logs ({} logs)", logs.len()), &merged);
+            if let Some(output) = output {
+                let merged = hdr::merged_intervals(merged);
+                let start = merged.iter().map(|i| i.start).min().unwrap_or_else(std::time::SystemTime::now);
+                hdr::write_log(&mut std::fs::File::create(output)?, start, &merged)?;
+                println!("\nMerged log written to {}", output.display());
+            }
+            return Ok(());
+        }
+        Commands::Coordinator {
+            agents,
+            model,
+            requests,
+            concurrency,
+            streaming_off,
+            start_delay,
+            token,
+        } => {
+            let config = CoordinatorConfig {
+                agents: agents.clone(),
+                models: model.clone(),
+                requests: *requests,
+                concurrency: *concurrency,
+                streaming: !streaming_off,
+                token: token.clone(),
+                start_delay: *start_delay,
+                hdr_log: cli.hdr_log.clone(),
+            };
+            distributed::run_coordinator(config).await?;
+            return Ok(());
+        }
+        _ => {}
+    }
 
     // Load environment variables
     if dotenv().is_err() {
         info!("No .env file found, using system environment variables");
     }
 
-    let cli = Cli::parse();
-
     // Get API key from environment
     let api_key = env::var("SUDO_API_KEY")
         .map_err(|_| anyhow::anyhow!("SUDO_API_KEY environment variable is required"))?;
@@ -82,7 +635,65 @@ async fn main() -> Result<()> {
 
     info!("Using API base URL: {}", base_url);
 
-    let runner = BenchmarkRunner::new(api_key, base_url).await?;
+    let goodput_slo = cli.goodput_slo();
+
+    // Long-running probers and agents, and `models`, produce no per-run stats
+    let records_history = !cli.no_history
+        && !matches!(
+            cli.command,
+            Commands::Models | Commands::ServeMetrics { .. } | Commands::Monitor { .. } | Commands::Agent { .. }
+        );
+    let history = if records_history {
+        let store = Arc::new(HistoryStore::open(&cli.history_db)?);
+        let args: Vec<String> = env::args().skip(1).collect();
+        Some(HistoryRecorder::new(
+            store,
+            matches.subcommand_name().unwrap_or_default(),
+            &args.join(" "),
+            &base_url,
+            cli.history_samples,
+        ))
+    } else {
+        None
+    };
+
+    let tokenizers = Arc::new(TokenizerRegistry::new(cli.tokenizer)?);
+    let cost = Arc::new(CostTracker::new(PricingTable::load(cli.pricing.as_deref())?, cli.max_cost));
+    let mut limits = RateLimits {
+        global: RateLimit {
+            requests_per_minute: cli.rpm,
+            tokens_per_minute: cli.tpm,
+        },
+        ..RateLimits::default()
+    };
+    for (model, rpm) in cli.model_rpm {
+        limits.per_model.entry(model).or_default().requests_per_minute = Some(rpm);
+    }
+    for (model, tpm) in cli.model_tpm {
+        limits.per_model.entry(model).or_default().tokens_per_minute = Some(tpm);
+    }
+    let limiter = Arc::new(RateLimiter::new(limits));
+    let client_config = ClientConfig {
+        api_key,
+        base_url,
+        reasoning_effort: cli.reasoning_effort,
+        phase_timing: cli.phase_timing,
+        http_version: cli.http_version,
+        timeouts: Timeouts {
+            connect: cli.connect_timeout,
+            first_byte: cli.ttfb_timeout,
+            idle: cli.idle_timeout,
+            total: cli.total_timeout,
+        },
+        cost: Arc::clone(&cost),
+        limiter: Arc::clone(&limiter),
+    };
+    let hdr_log = cli.hdr_log.map(|path| Arc::new(HdrLog::new(path)));
+    let runner = BenchmarkRunner::new(client_config, cli.connection_mode, tokenizers)
+        .await?
+        .with_history(history.clone())
+        .with_hdr_log(hdr_log.clone())
+        .with_goodput_slo(goodput_slo);
 
     match cli.command {
         Commands::Latency {
@@ -101,9 +712,224 @@ async fn main() -> Result<()> {
             let config = BenchmarkConfig::throughput(concurrency, model);
             runner.run_throughput_benchmark(config).await?;
         }
+        Commands::AuditUsage { model, tolerance } => {
+            runner.run_usage_audit(model, tolerance).await?;
+        }
+        Commands::PrefillSweep {
+            model,
+            lengths,
+            requests,
+            concurrency,
+            context_windows,
+        } => {
+            let config = PrefillSweepConfig {
+                model,
+                lengths,
+                requests,
+                concurrency,
+                context_windows: context_windows.into_iter().collect(),
+            };
+            runner.run_prefill_sweep(config).await?;
+        }
+        Commands::DecodeSweep {
+            model,
+            output_lengths,
+            requests,
+            concurrency,
+        } => {
+            let config = DecodeSweepConfig {
+                model,
+                output_lengths,
+                requests,
+                concurrency,
+            };
+            runner.run_decode_sweep(config).await?;
+        }
+        Commands::Conversation {
+            model,
+            turns,
+            conversations,
+            concurrency,
+            streaming_off,
+        } => {
+            let config = ConversationConfig {
+                model,
+                turns,
+                conversations,
+                concurrency,
+                streaming: !streaming_off,
+            };
+            runner.run_conversation_benchmark(config).await?;
+        }
+        Commands::Cache {
+            model,
+            prefix_tokens,
+            rounds,
+            warm_requests,
+            settle_ms,
+        } => {
+            let config = CacheConfig {
+                model,
+                prefix_tokens,
+                rounds,
+                warm_requests,
+                settle: Duration::from_millis(settle_ms),
+            };
+            runner.run_cache_benchmark(config).await?;
+        }
+        Commands::Tools {
+            model,
+            requests,
+            concurrency,
+            tool_choice,
+        } => {
+            let config = ToolConfig {
+                model,
+                requests,
+                concurrency,
+                tool_choice,
+            };
+            runner.run_tool_benchmark(config).await?;
+        }
+        Commands::Structured {
+            model,
+            requests,
+            concurrency,
+            json_object,
+        } => {
+            let config = StructuredConfig {
+                model,
+                requests,
+                concurrency,
+                json_schema: !json_object,
+            };
+            runner.run_structured_benchmark(config).await?;
+        }
+        Commands::Embeddings {
+            model,
+            batch_sizes,
+            input_tokens,
+            requests,
+            concurrency,
+        } => {
+            let config = EmbeddingConfig {
+                model,
+                batch_sizes,
+                input_tokens,
+                requests,
+                concurrency,
+            };
+            runner.run_embeddings_benchmark(config).await?;
+        }
+        Commands::Vision {
+            model,
+            image,
+            resolutions,
+            image_counts,
+            requests,
+            concurrency,
+        } => {
+            let config = VisionConfig {
+                model,
+                images: image,
+                resolutions,
+                image_counts,
+                requests,
+                concurrency,
+            };
+            runner.run_vision_benchmark(config).await?;
+        }
+        Commands::Connections {
+            model,
+            modes,
+            requests,
+            concurrency,
+        } => {
+            let config = ConnectionConfig {
+                model,
+                modes,
+                requests,
+                concurrency,
+            };
+            runner.run_connection_benchmark(config).await?;
+        }
+        Commands::Protocols {
+            model,
+            versions,
+            requests,
+            concurrency,
+        } => {
+            let config = ProtocolConfig {
+                model,
+                versions,
+                requests,
+                concurrency,
+            };
+            runner.run_protocol_benchmark(config).await?;
+        }
+        Commands::ServeMetrics {
+            listen,
+            interval,
+            model,
+            requests,
+            concurrency,
+        } => {
+            let config = ServeMetricsConfig {
+                listen,
+                interval,
+                model,
+                requests,
+                concurrency,
+            };
+            runner.run_metrics_server(config).await?;
+        }
+        Commands::Monitor {
+            interval,
+            model,
+            requests,
+            concurrency,
+            window,
+            slo_p95_ttfc,
+            slo_success_rate,
+            webhook,
+        } => {
+            let config = MonitorConfig {
+                interval,
+                model,
+                requests,
+                concurrency,
+                slo: SloConfig {
+                    window,
+                    max_p95_ttfc: slo_p95_ttfc,
+                    min_success_rate: slo_success_rate,
+                },
+                webhook,
+            };
+            runner.run_monitor(config).await?;
+        }
+        Commands::Report {
+            html,
+            model,
+            requests,
+            concurrency,
+        } => {
+            let config = ReportConfig {
+                model,
+                requests,
+                concurrency,
+                html,
+            };
+            runner.run_report(config).await?;
+        }
         Commands::Models => {
             runner.list_models().await?;
         }
+        Commands::Agent { listen, token } => {
+            runner.run_agent(AgentConfig { listen, token }).await?;
+        }
+        Commands::History { .. } | Commands::Merge { .. } | Commands::Coordinator { .. } => {
+            unreachable!("handled before connecting")
+        }
         Commands::All {
             latency_requests,
             concurrency,
@@ -114,5 +940,21 @@ async fn main() -> Result<()> {
         }
     }
 
+    if limiter.is_enabled() {
+        limiter.print_summary();
+    }
+
+    if !cost.is_empty() {
+        cost.print_summary();
+        if let Some(history) = &history {
+            cost.record_history(history);
+        }
+    }
+
+    if let Some(hdr_log) = hdr_log {
+        hdr_log.write()?;
+        info!("HdrHistogram log written to {}", hdr_log.path().display());
+    }
+
     Ok(())
 }
@@ -1,6 +1,12 @@
-use std::time::Duration;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
 use hdrhistogram::Histogram;
 
+use crate::client::{failure_label, RequestTimeout, TimeoutPhase};
+use crate::models::{ToolCall, Usage};
+use crate::phases::{self, PhaseStats, PhaseTimings};
+use crate::tokenizer::{TokenSource, TokenSourceCounts};
+
 #[derive(Debug, Clone)]
 pub struct LatencyMetric {
     pub total_duration: Duration,
@@ -9,28 +15,97 @@ pub struct LatencyMetric {
     pub request_size: usize,
     #[allow(dead_code)]
     pub response_size: usize,
+    // Present when the client was built with phase timing
+    pub phases: Option<PhaseTimings>,
+    // Negotiated protocol, e.g. "HTTP/1.1" or "HTTP/2.0"
+    pub http_version: String,
     pub model: String,
+    // Time held by the client-side rate limiter before the request was sent; not part of
+    // `total_duration`
+    pub limiter_wait: Duration,
 }
 
 #[derive(Debug, Clone)]
 pub struct StreamingMetric {
     pub total_duration: Duration,
+    // Until response headers
+    pub time_to_first_byte: Duration,
     pub time_to_first_chunk: Option<Duration>,
     pub chunk_count: u32,
     pub total_tokens: u32,
+    pub token_source: TokenSource,
+    // Usage from the final stream chunk, if the server sent one
+    pub usage: Option<Usage>,
+    pub output_text: String,
+    pub finish_reason: Option<String>,
+    pub tool_calls: Vec<ToolCall>,
+    pub time_to_first_tool_call: Option<Duration>,
+    // When the last tool-call argument fragment arrived
+    pub time_to_tool_call_complete: Option<Duration>,
+    // Set when the model reasoned (streamed reasoning deltas or reported reasoning tokens)
+    pub reasoning: Option<ReasoningMetric>,
+    pub phases: Option<PhaseTimings>,
+    pub http_version: String,
     pub model: String,
     #[allow(dead_code)]
     pub request_size: usize,
+    // Time held by the client-side rate limiter; not part of any duration above
+    pub limiter_wait: Duration,
+}
+
+impl StreamingMetric {
+    // Mean gap between chunks after the first
+    pub fn inter_token_latency(&self) -> Option<Duration> {
+        let first = self.time_to_first_chunk?;
+        (self.chunk_count > 1).then(|| self.total_duration.saturating_sub(first) / (self.chunk_count - 1))
+    }
+
+    // Mean time per output token after the first
+    pub fn time_per_output_token(&self) -> Option<Duration> {
+        let first = self.time_to_first_chunk?;
+        (self.total_tokens > 1).then(|| self.total_duration.saturating_sub(first) / (self.total_tokens - 1))
+    }
+
+    // Tokens over the time from the first chunk to the end of the stream
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        let generation = self.total_duration.saturating_sub(self.time_to_first_chunk?).as_secs_f64();
+        (generation > 0.0).then(|| self.total_tokens as f64 / generation)
+    }
+}
+
+// Splits a reasoning model's response into hidden thinking and visible output.
+#[derive(Debug, Clone, Copy)]
+pub struct ReasoningMetric {
+    pub reasoning_tokens: u32,
+    // From the first reasoning delta (or the first chunk, when reasoning isn't streamed)
+    // until the first visible content
+    pub thinking_duration: Duration,
+    pub time_to_first_visible_token: Option<Duration>,
+    pub visible_tokens: u32,
+    // Visible tokens over the time from first visible content to end of stream
+    pub visible_tokens_per_second: f64,
 }
 
 #[derive(Debug, Clone)]
 pub struct ThroughputMetric {
+    // Generation time after the first chunk when streaming, the whole request otherwise
     pub duration: Duration,
+    pub time_to_first_chunk: Option<Duration>,
+    pub tokens: u32,
     pub successful_requests: u64,
     pub failed_requests: u64,
     pub tokens_per_second: f64,
+    #[allow(dead_code)]
     pub requests_per_second: f64,
+    // None when the request failed and no tokens were counted
+    pub token_source: Option<TokenSource>,
+    pub reasoning: Option<ReasoningMetric>,
+    // Set when the request failed because a deadline fired
+    pub timeout: Option<RequestTimeout>,
+    // `client::failure_label` of a failed request
+    pub failure: Option<String>,
     pub model: String,
+    pub limiter_wait: Duration,
 }
 
 #[allow(dead_code)]
@@ -46,6 +121,12 @@ pub struct LatencyStats {
     pub p99_latency: Duration,
     pub mean_ttfb: Duration,
     pub p95_ttfb: Duration,
+    pub phases: Option<PhaseStats>,
+    // Negotiated protocol -> requests
+    pub http_versions: BTreeMap<String, usize>,
+    pub timeouts: Option<TimeoutStats>,
+    pub goodput: Option<GoodputStats>,
+    pub limiter_wait: Option<LimiterWaitStats>,
 }
 
 #[allow(dead_code)]
@@ -56,6 +137,25 @@ pub struct StreamingStats {
     pub mean_time_to_first_chunk: Duration,
     pub p95_time_to_first_chunk: Duration,
     pub total_chunks: u32,
+    pub token_sources: TokenSourceCounts,
+    pub reasoning: Option<ReasoningStats>,
+    pub phases: Option<PhaseStats>,
+    pub http_versions: BTreeMap<String, usize>,
+    pub timeouts: Option<TimeoutStats>,
+    pub goodput: Option<GoodputStats>,
+    pub limiter_wait: Option<LimiterWaitStats>,
+}
+
+#[derive(Debug)]
+pub struct ReasoningStats {
+    pub reasoning_requests: usize,
+    pub mean_reasoning_tokens: u32,
+    pub mean_visible_tokens: u32,
+    pub mean_thinking_duration: Duration,
+    pub p95_thinking_duration: Duration,
+    pub mean_time_to_first_visible_token: Duration,
+    pub p95_time_to_first_visible_token: Duration,
+    pub mean_visible_tokens_per_second: f64,
 }
 
 #[derive(Debug)]
@@ -66,16 +166,285 @@ pub struct ThroughputStats {
     pub total_requests: u64,
     pub successful_requests: u64,
     pub failed_requests: u64,
+    #[allow(dead_code)]
     pub mean_requests_per_second: f64,
     pub mean_tokens_per_second: f64,
     pub success_rate: f64,
+    pub token_sources: TokenSourceCounts,
+    pub reasoning: Option<ReasoningStats>,
+    pub timeouts: Option<TimeoutStats>,
+    pub goodput: Option<GoodputStats>,
+    pub limiter_wait: Option<LimiterWaitStats>,
+}
+
+// Per-request objectives for goodput. A request is good when it succeeded and met every limit
+// that is set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GoodputSlo {
+    pub max_ttft: Option<Duration>,
+    pub max_tpot: Option<Duration>,
+    pub max_latency: Option<Duration>,
+}
+
+impl GoodputSlo {
+    // The limits a successful request missed. TPOT is only checked when it could be measured.
+    fn missed(&self, sample: &GoodputSample) -> Vec<&'static str> {
+        let mut missed = Vec::new();
+        if self.max_ttft.is_some_and(|max| sample.ttft > max) {
+            missed.push("ttft");
+        }
+        if let (Some(max), Some(tpot)) = (self.max_tpot, sample.tpot) {
+            if tpot > max {
+                missed.push("tpot");
+            }
+        }
+        if self.max_latency.is_some_and(|max| sample.latency > max) {
+            missed.push("latency");
+        }
+        missed
+    }
+}
+
+// One successful request as the SLO sees it. Without a first chunk (non-streaming), TTFT is
+// the whole latency.
+#[derive(Debug, Clone, Copy)]
+pub struct GoodputSample {
+    pub ttft: Duration,
+    pub tpot: Option<Duration>,
+    pub latency: Duration,
+    pub tokens: Option<u32>,
+}
+
+impl GoodputSample {
+    pub fn streaming(metric: &StreamingMetric) -> Self {
+        Self {
+            ttft: metric.time_to_first_chunk.unwrap_or(metric.total_duration),
+            tpot: metric.time_per_output_token(),
+            latency: metric.total_duration,
+            tokens: Some(metric.total_tokens),
+        }
+    }
+
+    // A request with nothing streamed back, such as an embeddings call
+    pub fn request(latency: Duration) -> Self {
+        Self {
+            ttft: latency,
+            tpot: None,
+            latency,
+            tokens: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GoodputStats {
+    // Including failed requests
+    pub requests: usize,
+    pub good_requests: usize,
+    // Percent of requests that met the SLO
+    pub attainment: f64,
+    // Good requests and their output tokens over the wall time of the test
+    pub goodput_requests_per_second: f64,
+    // None when token counts aren't known (non-streaming latency requests)
+    pub goodput_tokens_per_second: Option<f64>,
+    // Requests that missed each objective; failed requests count as "error"
+    pub missed: BTreeMap<&'static str, usize>,
+}
+
+impl std::fmt::Display for GoodputStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1}% met SLO ({}/{}), {:.2} req/s",
+            self.attainment, self.good_requests, self.requests, self.goodput_requests_per_second
+        )?;
+        if let Some(tps) = self.goodput_tokens_per_second {
+            write!(f, ", {:.1} tokens/s", tps)?;
+        }
+        if !self.missed.is_empty() {
+            let missed: Vec<String> = self.missed.iter().map(|(name, count)| format!("{} {}", name, count)).collect();
+            write!(f, "; missed {}", missed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Every report takes percentiles the same way: the value at index `n * percent / 100` of the
+// ascending `sorted` values, so the median is the upper middle value. Zero when empty.
+pub fn percentile<T: Copy + Default>(sorted: &[T], percent: usize) -> T {
+    if sorted.is_empty() {
+        return T::default();
+    }
+    sorted[(sorted.len() * percent / 100).min(sorted.len() - 1)]
+}
+
+// Zero when empty
+pub fn mean_duration(values: &[Duration]) -> Duration {
+    if values.is_empty() {
+        Duration::ZERO
+    } else {
+        values.iter().sum::<Duration>() / values.len() as u32
+    }
+}
+
+// Zero when empty
+pub fn mean_f64(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+// Mean of per-point means, weighted by each point's sample count. Zero when empty.
+pub fn weighted_mean(values: impl IntoIterator<Item = (f64, usize)>) -> f64 {
+    let (sum, count) = values
+        .into_iter()
+        .fold((0.0, 0), |(sum, count), (value, weight)| (sum + value * weight as f64, count + weight));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+// Sorts `values` and returns their mean and `percent` percentile
+pub fn mean_and_percentile(mut values: Vec<Duration>, percent: usize) -> (Duration, Duration) {
+    values.sort();
+    (mean_duration(&values), percentile(&values, percent))
+}
+
+// Flattened (metric, value) pairs for the history store; durations are in milliseconds
+impl LatencyStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("requests", self.request_count as f64),
+            ("mean_latency", ms(self.mean_latency)),
+            ("p50_latency", ms(self.p50_latency)),
+            ("p95_latency", ms(self.p95_latency)),
+            ("p99_latency", ms(self.p99_latency)),
+            ("mean_ttfb", ms(self.mean_ttfb)),
+            ("p95_ttfb", ms(self.p95_ttfb)),
+        ]
+        .into_iter()
+        .chain(self.goodput.iter().flat_map(GoodputStats::history_metrics))
+        .chain(self.limiter_wait.iter().flat_map(LimiterWaitStats::history_metrics))
+        .collect()
+    }
+}
+
+impl StreamingStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("requests", self.request_count as f64),
+            ("mean_ttfc", ms(self.mean_time_to_first_chunk)),
+            ("p95_ttfc", ms(self.p95_time_to_first_chunk)),
+        ]
+        .into_iter()
+        .chain(self.goodput.iter().flat_map(GoodputStats::history_metrics))
+        .chain(self.limiter_wait.iter().flat_map(LimiterWaitStats::history_metrics))
+        .collect()
+    }
+}
+
+impl ThroughputStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("requests", self.total_requests as f64),
+            ("success_rate", self.success_rate),
+            ("tokens_per_second", self.mean_tokens_per_second),
+            ("mean_duration", ms(self.test_duration)),
+        ]
+        .into_iter()
+        .chain(self.goodput.iter().flat_map(GoodputStats::history_metrics))
+        .chain(self.limiter_wait.iter().flat_map(LimiterWaitStats::history_metrics))
+        .collect()
+    }
+}
+
+impl LimiterWaitStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![("mean_limiter_wait", ms(self.mean_wait)), ("p95_limiter_wait", ms(self.p95_wait))]
+    }
+}
+
+impl GoodputStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        let mut metrics = vec![
+            ("slo_attainment", self.attainment),
+            ("goodput_rps", self.goodput_requests_per_second),
+        ];
+        if let Some(tps) = self.goodput_tokens_per_second {
+            metrics.push(("goodput_tps", tps));
+        }
+        metrics
+    }
+}
+
+// Time requests spent in the client-side rate limiter, kept out of latency
+#[derive(Debug)]
+pub struct LimiterWaitStats {
+    pub waited_requests: usize,
+    pub mean_wait: Duration,
+    pub p95_wait: Duration,
+    pub max_wait: Duration,
+}
+
+impl std::fmt::Display for LimiterWaitStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} requests waited (mean {:.2?}, P95 {:.2?}, max {:.2?})",
+            self.waited_requests, self.mean_wait, self.p95_wait, self.max_wait
+        )
+    }
+}
+
+// Requests that failed because a deadline fired, kept apart from other errors
+#[derive(Debug)]
+pub struct TimeoutStats {
+    pub count: usize,
+    pub phases: BTreeMap<TimeoutPhase, TimeoutPhaseStats>,
+}
+
+impl std::fmt::Display for TimeoutStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let phases: Vec<String> = self
+            .phases
+            .iter()
+            .map(|(phase, stats)| format!("{} {} at ~{:.2?}", stats.count, phase, stats.mean_fired_at))
+            .collect();
+        write!(f, "{} timed out: {}", self.count, phases.join(", "))
+    }
+}
+
+// When timeouts in one phase fired, measured from the start of the request
+#[derive(Debug)]
+pub struct TimeoutPhaseStats {
+    pub count: usize,
+    pub mean_fired_at: Duration,
+    pub max_fired_at: Duration,
 }
 
 pub struct MetricsCollector {
     latency_metrics: Vec<LatencyMetric>,
     streaming_metrics: Vec<StreamingMetric>,
     throughput_metrics: Vec<ThroughputMetric>,
+    // Successful requests of benchmarks that keep their own results, for goodput only
+    goodput_samples: Vec<GoodputSample>,
     errors: Vec<String>,
+    timeouts: Vec<RequestTimeout>,
+    // `client::failure_label` of every failed request
+    failures: Vec<String>,
+    // When collection began; the start of the interval in HdrHistogram logs
+    started_at: SystemTime,
+    // When the last result was added; with `started_at`, the wall time goodput is measured over
+    finished_at: SystemTime,
+    goodput_slo: Option<GoodputSlo>,
 }
 
 impl MetricsCollector {
@@ -84,24 +453,161 @@ impl MetricsCollector {
             latency_metrics: Vec::new(),
             streaming_metrics: Vec::new(),
             throughput_metrics: Vec::new(),
+            goodput_samples: Vec::new(),
             errors: Vec::new(),
+            timeouts: Vec::new(),
+            failures: Vec::new(),
+            started_at: SystemTime::now(),
+            finished_at: SystemTime::now(),
+            goodput_slo: None,
         }
     }
 
+    pub fn with_goodput_slo(mut self, slo: Option<GoodputSlo>) -> Self {
+        self.goodput_slo = slo;
+        self
+    }
+
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
     pub fn add_latency_metric(&mut self, metric: LatencyMetric) {
         self.latency_metrics.push(metric);
+        self.finished_at = SystemTime::now();
     }
 
     pub fn add_streaming_metric(&mut self, metric: StreamingMetric) {
         self.streaming_metrics.push(metric);
+        self.finished_at = SystemTime::now();
     }
 
     pub fn add_throughput_metric(&mut self, metric: ThroughputMetric) {
+        if let Some(failure) = &metric.failure {
+            self.failures.push(failure.clone());
+        }
         self.throughput_metrics.push(metric);
+        self.finished_at = SystemTime::now();
+    }
+
+    pub fn add_goodput_sample(&mut self, sample: GoodputSample) {
+        self.goodput_samples.push(sample);
+        self.finished_at = SystemTime::now();
     }
 
     pub fn add_error(&mut self, error: String) {
         self.errors.push(error);
+        self.failures.push("error".to_string());
+        self.finished_at = SystemTime::now();
+    }
+
+    pub fn streaming_metrics(&self) -> &[StreamingMetric] {
+        &self.streaming_metrics
+    }
+
+    pub fn latency_metrics(&self) -> &[LatencyMetric] {
+        &self.latency_metrics
+    }
+
+    pub fn throughput_metrics(&self) -> &[ThroughputMetric] {
+        &self.throughput_metrics
+    }
+
+    pub fn failures(&self) -> &[String] {
+        &self.failures
+    }
+
+    // Percent of latency and streaming requests that completed
+    pub fn success_rate(&self) -> f64 {
+        let succeeded = self.latency_metrics.len() + self.streaming_metrics.len();
+        let total = succeeded + self.failures.len();
+        if total == 0 { 0.0 } else { succeeded as f64 / total as f64 * 100.0 }
+    }
+
+    // Appended to "no successful requests" errors, where there are no stats to carry timeouts
+    pub fn timeout_note(&self) -> String {
+        match summarize_timeouts(&self.timeouts) {
+            Some(timeouts) => format!(" ({})", timeouts),
+            None => String::new(),
+        }
+    }
+
+    // Records a failed request, as a timeout when a deadline fired and as an error otherwise
+    pub fn add_request_error(&mut self, error: &anyhow::Error) {
+        self.failures.push(failure_label(error));
+        self.finished_at = SystemTime::now();
+        match error.downcast_ref::<RequestTimeout>() {
+            Some(timeout) => self.timeouts.push(*timeout),
+            None => self.errors.push(error.to_string()),
+        }
+    }
+
+    // Goodput of the given successful requests when an SLO is set. Failed requests are
+    // collected per test rather than per model, so all of them count against it.
+    fn calculate_goodput(&self, samples: impl Iterator<Item = GoodputSample>) -> Option<GoodputStats> {
+        let slo = self.goodput_slo?;
+        let wall_time = self.finished_at.duration_since(self.started_at).unwrap_or_default().as_secs_f64();
+        let per_second = |value: f64| if wall_time > 0.0 { value / wall_time } else { 0.0 };
+
+        let mut missed: BTreeMap<&'static str, usize> = BTreeMap::new();
+        let (mut requests, mut good_requests, mut good_tokens, mut tokens_known) = (0, 0, 0u64, true);
+        for sample in samples {
+            requests += 1;
+            let sample_missed = slo.missed(&sample);
+            if sample_missed.is_empty() {
+                good_requests += 1;
+                match sample.tokens {
+                    Some(tokens) => good_tokens += tokens as u64,
+                    None => tokens_known = false,
+                }
+            }
+            for name in sample_missed {
+                *missed.entry(name).or_insert(0) += 1;
+            }
+        }
+        if !self.failures.is_empty() {
+            missed.insert("error", self.failures.len());
+            requests += self.failures.len();
+        }
+
+        Some(GoodputStats {
+            requests,
+            good_requests,
+            attainment: if requests == 0 { 0.0 } else { good_requests as f64 / requests as f64 * 100.0 },
+            goodput_requests_per_second: per_second(good_requests as f64),
+            goodput_tokens_per_second: tokens_known.then(|| per_second(good_tokens as f64)),
+            missed,
+        })
+    }
+
+    // Goodput of the samples added with `add_goodput_sample` and every failed request
+    pub fn goodput(&self) -> Option<GoodputStats> {
+        self.calculate_goodput(self.goodput_samples.iter().copied())
+    }
+
+    // Microsecond histograms of a model's latency, TTFB, TTFC and inter-token latency, for
+    // the metrics that have samples
+    pub fn histograms(&self, model: &str) -> Vec<(&'static str, Histogram<u64>)> {
+        let latency = self.latency_metrics.iter().filter(|m| m.model == model);
+        let streaming: Vec<_> = self.streaming_metrics.iter().filter(|m| m.model == model).collect();
+
+        let histograms = [
+            (
+                "latency",
+                microsecond_histogram(
+                    latency.clone().map(|m| m.total_duration).chain(streaming.iter().map(|m| m.total_duration)),
+                ),
+            ),
+            (
+                "ttfb",
+                microsecond_histogram(
+                    latency.map(|m| m.time_to_first_byte).chain(streaming.iter().map(|m| m.time_to_first_byte)),
+                ),
+            ),
+            ("ttfc", microsecond_histogram(streaming.iter().filter_map(|m| m.time_to_first_chunk))),
+            ("itl", microsecond_histogram(streaming.iter().filter_map(|m| m.inter_token_latency()))),
+        ];
+        histograms.into_iter().filter(|(_, h)| !h.is_empty()).collect()
     }
 
     pub fn calculate_latency_stats(&self, model: &str) -> Option<LatencyStats> {
@@ -117,41 +623,46 @@ impl MetricsCollector {
 
         let mut latencies: Vec<u64> = model_metrics
             .iter()
-            .map(|m| m.total_duration.as_millis() as u64)
+            .map(|m| m.total_duration.as_micros() as u64)
             .collect();
         latencies.sort();
 
         let mut ttfbs: Vec<u64> = model_metrics
             .iter()
-            .map(|m| m.time_to_first_byte.as_millis() as u64)
+            .map(|m| m.time_to_first_byte.as_micros() as u64)
             .collect();
         ttfbs.sort();
 
-        let mut histogram = Histogram::<u64>::new(3).unwrap();
-        for &latency in &latencies {
-            histogram.record(latency).unwrap();
-        }
+        let histogram = microsecond_histogram(model_metrics.iter().map(|m| m.total_duration));
 
-        let mean_latency = Duration::from_millis(
+        let mean_latency = Duration::from_micros(
             latencies.iter().sum::<u64>() / latencies.len() as u64
         );
-        let mean_ttfb = Duration::from_millis(
+        let mean_ttfb = Duration::from_micros(
             ttfbs.iter().sum::<u64>() / ttfbs.len() as u64
         );
 
         Some(LatencyStats {
             model: model.to_string(),
             request_count: model_metrics.len(),
-            min_latency: Duration::from_millis(*latencies.first().unwrap()),
-            max_latency: Duration::from_millis(*latencies.last().unwrap()),
+            min_latency: Duration::from_micros(*latencies.first().unwrap()),
+            max_latency: Duration::from_micros(*latencies.last().unwrap()),
             mean_latency,
-            p50_latency: Duration::from_millis(histogram.value_at_quantile(0.5)),
-            p95_latency: Duration::from_millis(histogram.value_at_quantile(0.95)),
-            p99_latency: Duration::from_millis(histogram.value_at_quantile(0.99)),
+            p50_latency: Duration::from_micros(histogram.value_at_quantile(0.5)),
+            p95_latency: Duration::from_micros(histogram.value_at_quantile(0.95)),
+            p99_latency: Duration::from_micros(histogram.value_at_quantile(0.99)),
             mean_ttfb,
-            p95_ttfb: Duration::from_millis(
-                *ttfbs.get((ttfbs.len() * 95 / 100).min(ttfbs.len().saturating_sub(1))).unwrap_or(&0)
+            p95_ttfb: Duration::from_micros(percentile(&ttfbs, 95)),
+            phases: phases::summarize(
+                &model_metrics
+                    .iter()
+                    .filter_map(|m| m.phases.map(|p| (m.total_duration, p)))
+                    .collect::<Vec<_>>(),
             ),
+            http_versions: count_http_versions(model_metrics.iter().map(|m| m.http_version.as_str())),
+            timeouts: summarize_timeouts(&self.timeouts),
+            limiter_wait: summarize_limiter_waits(model_metrics.iter().map(|m| m.limiter_wait)),
+            goodput: self.calculate_goodput(model_metrics.iter().map(|m| GoodputSample::request(m.total_duration))),
         })
     }
 
@@ -172,15 +683,14 @@ impl MetricsCollector {
             .collect();
         ttfcs.sort();
 
-        let mean_ttfc = if ttfcs.is_empty() {
-            Duration::from_millis(0)
-        } else {
-            Duration::from_nanos(
-                ttfcs.iter().map(|d| d.as_nanos() as u64).sum::<u64>() / ttfcs.len() as u64
-            )
-        };
+        let mean_ttfc = mean_duration(&ttfcs);
+
+        let p95_ttfc = percentile(&ttfcs, 95);
 
-        let p95_ttfc = ttfcs.get(ttfcs.len() * 95 / 100).copied().unwrap_or(Duration::from_millis(0));
+        let mut token_sources = TokenSourceCounts::default();
+        for metric in &model_metrics {
+            token_sources.record(metric.token_source);
+        }
 
         Some(StreamingStats {
             model: model.to_string(),
@@ -188,6 +698,18 @@ impl MetricsCollector {
             mean_time_to_first_chunk: mean_ttfc,
             p95_time_to_first_chunk: p95_ttfc,
             total_chunks: model_metrics.iter().map(|m| m.chunk_count).sum(),
+            token_sources,
+            reasoning: summarize_reasoning(model_metrics.iter().filter_map(|m| m.reasoning)),
+            phases: phases::summarize(
+                &model_metrics
+                    .iter()
+                    .filter_map(|m| m.phases.map(|p| (m.total_duration, p)))
+                    .collect::<Vec<_>>(),
+            ),
+            http_versions: count_http_versions(model_metrics.iter().map(|m| m.http_version.as_str())),
+            timeouts: summarize_timeouts(&self.timeouts),
+            limiter_wait: summarize_limiter_waits(model_metrics.iter().map(|m| m.limiter_wait)),
+            goodput: self.calculate_goodput(model_metrics.iter().map(|m| GoodputSample::streaming(m))),
         })
     }
 
@@ -215,6 +737,11 @@ impl MetricsCollector {
             0.0
         };
 
+        let mut token_sources = TokenSourceCounts::default();
+        for source in successful_metrics.iter().filter_map(|m| m.token_source) {
+            token_sources.record(source);
+        }
+
         // For single-request tests, requests per second doesn't make as much sense
         // But we can calculate average request duration
         let mean_duration = if !successful_metrics.is_empty() {
@@ -238,6 +765,19 @@ impl MetricsCollector {
             } else { 
                 0.0 
             },
+            token_sources,
+            reasoning: summarize_reasoning(successful_metrics.iter().filter_map(|m| m.reasoning)),
+            timeouts: summarize_timeouts(&model_metrics.iter().filter_map(|m| m.timeout).collect::<Vec<_>>()),
+            limiter_wait: summarize_limiter_waits(successful_metrics.iter().map(|m| m.limiter_wait)),
+            goodput: self.calculate_goodput(successful_metrics.iter().map(|m| {
+                let latency = m.time_to_first_chunk.unwrap_or_default() + m.duration;
+                GoodputSample {
+                    ttft: m.time_to_first_chunk.unwrap_or(latency),
+                    tpot: (m.time_to_first_chunk.is_some() && m.tokens > 1).then(|| m.duration / (m.tokens - 1)),
+                    latency,
+                    tokens: Some(m.tokens),
+                }
+            })),
         })
     }
 
@@ -260,3 +800,188 @@ impl MetricsCollector {
         model_list
     }
 }
+
+// None when no request waited, so runs without limits report nothing extra
+fn summarize_limiter_waits(waits: impl Iterator<Item = Duration>) -> Option<LimiterWaitStats> {
+    let mut waits: Vec<Duration> = waits.collect();
+    let waited_requests = waits.iter().filter(|w| !w.is_zero()).count();
+    if waited_requests == 0 {
+        return None;
+    }
+    waits.sort();
+    Some(LimiterWaitStats {
+        waited_requests,
+        mean_wait: waits.iter().sum::<Duration>() / waits.len() as u32,
+        p95_wait: percentile(&waits, 95),
+        max_wait: waits[waits.len() - 1],
+    })
+}
+
+// None when no request timed out; otherwise fired-at times grouped by the phase that ran out
+fn summarize_timeouts(timeouts: &[RequestTimeout]) -> Option<TimeoutStats> {
+    if timeouts.is_empty() {
+        return None;
+    }
+
+    let mut by_phase: BTreeMap<TimeoutPhase, Vec<Duration>> = BTreeMap::new();
+    for timeout in timeouts {
+        by_phase.entry(timeout.phase).or_default().push(timeout.fired_at);
+    }
+
+    Some(TimeoutStats {
+        count: timeouts.len(),
+        phases: by_phase
+            .into_iter()
+            .map(|(phase, fired)| {
+                let stats = TimeoutPhaseStats {
+                    count: fired.len(),
+                    mean_fired_at: fired.iter().sum::<Duration>() / fired.len() as u32,
+                    max_fired_at: fired.iter().copied().max().unwrap_or_default(),
+                };
+                (phase, stats)
+            })
+            .collect(),
+    })
+}
+
+// Auto-resizing, 3 significant digits, one count per duration in microseconds
+pub fn microsecond_histogram(durations: impl Iterator<Item = Duration>) -> Histogram<u64> {
+    let mut histogram = Histogram::<u64>::new(3).unwrap();
+    for duration in durations {
+        histogram.record(duration.as_micros() as u64).unwrap();
+    }
+    histogram
+}
+
+pub fn count_http_versions<'a>(versions: impl Iterator<Item = &'a str>) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for version in versions {
+        *counts.entry(version.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+// None when no request reasoned, so non-reasoning models report nothing extra
+fn summarize_reasoning(metrics: impl Iterator<Item = ReasoningMetric>) -> Option<ReasoningStats> {
+    let metrics: Vec<ReasoningMetric> = metrics.collect();
+    if metrics.is_empty() {
+        return None;
+    }
+    let n = metrics.len();
+
+    let mut thinking: Vec<Duration> = metrics.iter().map(|m| m.thinking_duration).collect();
+    thinking.sort();
+    let mut visible_ttfts: Vec<Duration> = metrics.iter().filter_map(|m| m.time_to_first_visible_token).collect();
+    visible_ttfts.sort();
+
+    Some(ReasoningStats {
+        reasoning_requests: n,
+        mean_reasoning_tokens: metrics.iter().map(|m| m.reasoning_tokens).sum::<u32>() / n as u32,
+        mean_visible_tokens: metrics.iter().map(|m| m.visible_tokens).sum::<u32>() / n as u32,
+        mean_thinking_duration: mean_duration(&thinking),
+        p95_thinking_duration: percentile(&thinking, 95),
+        mean_time_to_first_visible_token: mean_duration(&visible_ttfts),
+        p95_time_to_first_visible_token: percentile(&visible_ttfts, 95),
+        mean_visible_tokens_per_second: metrics.iter().map(|m| m.visible_tokens_per_second).sum::<f64>() / n as f64,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    pub r_squared: f64,
+}
+
+// Ordinary least squares fit of y = slope * x + intercept. Needs at least two distinct x values.
+pub fn linear_fit(points: &[(f64, f64)]) -> Option<LinearFit> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let syy: f64 = points.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let r_squared = if syy == 0.0 { 1.0 } else { (sxy * sxy) / (sxx * syy) };
+
+    Some(LinearFit {
+        slope,
+        intercept,
+        r_squared,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_fit_recovers_an_exact_line() {
+        let fit = linear_fit(&[(0.0, 50.0), (1.0, 52.0), (2.0, 54.0), (4.0, 58.0)]).unwrap();
+        assert!((fit.slope - 2.0).abs() < 1e-9);
+        assert!((fit.intercept - 50.0).abs() < 1e-9);
+        assert!((fit.r_squared - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn linear_fit_reports_how_well_noisy_points_fit() {
+        let fit = linear_fit(&[(1.0, 1.0), (2.0, 3.0), (3.0, 2.0), (4.0, 4.0)]).unwrap();
+        assert!((fit.slope - 0.8).abs() < 1e-9);
+        assert!((fit.intercept - 0.5).abs() < 1e-9);
+        assert!((fit.r_squared - 0.64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn linear_fit_needs_two_distinct_x_values() {
+        assert!(linear_fit(&[]).is_none());
+        assert!(linear_fit(&[(1.0, 2.0)]).is_none());
+        assert!(linear_fit(&[(1.0, 2.0), (1.0, 3.0)]).is_none());
+        // A flat line fits perfectly
+        assert_eq!(linear_fit(&[(1.0, 2.0), (2.0, 2.0)]).unwrap().r_squared, 1.0);
+    }
+
+    #[test]
+    fn added_samples_and_failures_count_towards_goodput() {
+        let slo = GoodputSlo {
+            max_ttft: Some(Duration::from_millis(500)),
+            max_tpot: Some(Duration::from_millis(50)),
+            max_latency: None,
+        };
+        let mut collector = MetricsCollector::new().with_goodput_slo(Some(slo));
+        let streamed = |ttft_ms, tpot_ms| GoodputSample {
+            ttft: Duration::from_millis(ttft_ms),
+            tpot: Some(Duration::from_millis(tpot_ms)),
+            latency: Duration::from_secs(2),
+            tokens: Some(20),
+        };
+        collector.add_goodput_sample(streamed(200, 20));
+        collector.add_goodput_sample(streamed(800, 20));
+        collector.add_goodput_sample(streamed(200, 80));
+        collector.add_error("Task error: cancelled".to_string());
+
+        let goodput = collector.goodput().unwrap();
+        assert_eq!(goodput.requests, 4);
+        assert_eq!(goodput.good_requests, 1);
+        assert_eq!(goodput.attainment, 25.0);
+        assert_eq!(goodput.missed.get("ttft"), Some(&1));
+        assert_eq!(goodput.missed.get("tpot"), Some(&1));
+        assert_eq!(goodput.missed.get("error"), Some(&1));
+
+        // Without token counts only requests/sec is known
+        let mut embeddings = MetricsCollector::new().with_goodput_slo(Some(slo));
+        embeddings.add_goodput_sample(GoodputSample::request(Duration::from_millis(100)));
+        assert!(embeddings.goodput().unwrap().goodput_tokens_per_second.is_none());
+        assert!(MetricsCollector::new().goodput().is_none());
+    }
+}
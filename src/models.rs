@@ -10,12 +10,208 @@ pub struct ChatCompletionRequest {
     pub stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream_options: Option<StreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    // "low" / "medium" / "high" for reasoning models; providers may accept others
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String,
-    pub content: String,
+    // Null for assistant messages that only carry tool calls
+    pub content: Option<MessageContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: Some(MessageContent::Text(content.to_string())),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    pub fn user(content: &str) -> Self {
+        Self::new("user", content)
+    }
+
+    // A user message with text followed by images, in the array-of-parts form
+    pub fn user_with_images(text: &str, image_urls: &[String]) -> Self {
+        let mut parts = vec![ContentPart::Text { text: text.to_string() }];
+        parts.extend(image_urls.iter().map(|url| ContentPart::ImageUrl {
+            image_url: ImageUrl { url: url.clone(), detail: None },
+        }));
+        Self {
+            content: Some(MessageContent::Parts(parts)),
+            ..Self::new("user", "")
+        }
+    }
+
+    pub fn assistant(content: &str) -> Self {
+        Self::new("assistant", content)
+    }
+
+    pub fn assistant_tool_calls(tool_calls: Vec<ToolCall>) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: None,
+            tool_calls: Some(tool_calls),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn tool_result(tool_call_id: &str, content: &str) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.to_string()),
+            ..Self::new("tool", content)
+        }
+    }
+
+    // Text content, or "" when there is none. For multi-part content this is the first
+    // text part; images are not included.
+    pub fn text(&self) -> &str {
+        match &self.content {
+            Some(MessageContent::Text(text)) => text,
+            Some(MessageContent::Parts(parts)) => parts
+                .iter()
+                .find_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .unwrap_or(""),
+            None => "",
+        }
+    }
+}
+
+// Either a plain string or an array of text / image parts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageUrl {
+    // An http(s) URL or a `data:image/...;base64,` URL
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tool {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: FunctionDefinition,
+}
+
+impl Tool {
+    pub fn function(name: &str, description: &str, parameters: serde_json::Value) -> Self {
+        Self {
+            kind: "function".to_string(),
+            function: FunctionDefinition {
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                parameters,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionDefinition {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub parameters: serde_json::Value,
+}
+
+// Either a mode ("auto", "none", "required") or a specific function to call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolChoice {
+    Mode(String),
+    Function {
+        #[serde(rename = "type")]
+        kind: String,
+        function: ToolChoiceFunction,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolChoiceFunction {
+    pub name: String,
+}
+
+// `{"type": "json_object"}` or `{"type": "json_schema", "json_schema": {...}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseFormat {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<JsonSchemaFormat>,
+}
+
+impl ResponseFormat {
+    pub fn json_object() -> Self {
+        Self {
+            kind: "json_object".to_string(),
+            json_schema: None,
+        }
+    }
+
+    pub fn json_schema(name: &str, schema: serde_json::Value, strict: bool) -> Self {
+        Self {
+            kind: "json_schema".to_string(),
+            json_schema: Some(JsonSchemaFormat {
+                name: name.to_string(),
+                schema,
+                strict: Some(strict),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    pub arguments: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,7 +241,36 @@ pub struct Choice {
 pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: Option<u32>,
+    // Some providers leave it out of the final stream chunk
+    #[serde(default)]
     pub total_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+}
+
+impl Usage {
+    pub fn cached_tokens(&self) -> Option<u32> {
+        self.prompt_tokens_details.as_ref().and_then(|d| d.cached_tokens)
+    }
+
+    // Included in `completion_tokens` by the providers that report it
+    pub fn reasoning_tokens(&self) -> Option<u32> {
+        self.completion_tokens_details.as_ref().and_then(|d| d.reasoning_tokens)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTokensDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionTokensDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +279,8 @@ pub struct SupportedModel {
     pub model_provider: String,
     pub created_at: Option<String>,
     pub sudo_model_id: u32,
+    #[serde(default, alias = "context_window", skip_serializing_if = "Option::is_none")]
+    pub context_length: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,6 +288,38 @@ pub struct ModelsResponse {
     pub data: Vec<SupportedModel>,
 }
 
+// Sudo model names sometimes carry a provider prefix, e.g. "openai/gpt-4o"; this drops it
+pub fn base_model_name(model: &str) -> &str {
+    model.rsplit('/').next().unwrap_or(model)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingRequest {
+    pub model: String,
+    pub input: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingResponse {
+    pub data: Vec<EmbeddingData>,
+    pub model: String,
+    pub usage: Option<EmbeddingUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingData {
+    pub index: u32,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingUsage {
+    pub prompt_tokens: u32,
+    pub total_tokens: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageGenerationRequest {
     pub prompt: String,
@@ -71,12 +330,14 @@ pub struct ImageGenerationRequest {
     pub size: Option<String>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageGenerationResponse {
     pub created: i64,
     pub data: Vec<ImageData>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageData {
     pub url: Option<String>,
@@ -95,14 +356,23 @@ pub struct StreamingEvent {
 impl ChatCompletionRequest {
     pub fn simple_text_request(model: &str, message: &str, streaming: bool) -> Self {
         Self {
-            messages: vec![ChatMessage {
-                role: "user".to_string(),
-                content: message.to_string(),
-            }],
+            messages: vec![ChatMessage::user(message)],
             model: model.to_string(),
             max_completion_tokens: Some(150),
             stream: if streaming { Some(true) } else { None },
             stream_options: None,
+            tools: None,
+            tool_choice: None,
+            response_format: None,
+            reasoning_effort: None,
+        }
+    }
+
+    // A multi-turn chat: the full history so far, ending with the newest user message.
+    pub fn conversation_request(model: &str, messages: Vec<ChatMessage>, streaming: bool) -> Self {
+        Self {
+            messages,
+            ..Self::simple_text_request(model, "", streaming)
         }
     }
 
@@ -127,4 +397,35 @@ impl ChatCompletionRequest {
         req.max_completion_tokens = Some(512);
         req
     }
+
+    // For prefill sweeps: a caller-supplied (long) prompt with a tiny completion so TTFT is
+    // dominated by input processing.
+    pub fn benchmark_prefill_request(model: &str, prompt: &str) -> Self {
+        let mut req = Self::simple_text_request(model, prompt, true);
+        req.max_completion_tokens = Some(8);
+        req
+    }
+
+    // For decode sweeps: ask for far more text than `max_tokens` allows so generation
+    // normally runs until the cap (finish_reason "length").
+    pub fn benchmark_decode_request(model: &str, max_tokens: u32) -> Self {
+        let prompt = format!(
+            "Write a long, detailed essay of at least {} words on the history of computer networking, \
+             covering every decade in depth. Do not summarize, do not conclude early, and keep writing.",
+            max_tokens * 2
+        );
+        let mut req = Self::simple_text_request(model, &prompt, true);
+        req.max_completion_tokens = Some(max_tokens);
+        req
+    }
+}
+
+impl EmbeddingRequest {
+    pub fn new(model: &str, input: Vec<String>) -> Self {
+        Self {
+            model: model.to_string(),
+            input,
+            encoding_format: Some("float".to_string()),
+        }
+    }
 }
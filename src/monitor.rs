@@ -0,0 +1,215 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::metrics::{percentile, MetricsCollector};
+
+// Objectives evaluated over a sliding window of probe results: p95 TTFC must stay below
+// `max_p95_ttfc` and the success rate (percent) above `min_success_rate`.
+#[derive(Debug, Clone)]
+pub struct SloConfig {
+    pub window: Duration,
+    pub max_p95_ttfc: Duration,
+    pub min_success_rate: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ProbeOutcome {
+    at: Instant,
+    // None when the request failed
+    ttfc: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SloEvaluation {
+    pub requests: usize,
+    pub success_rate: f64,
+    pub p95_ttfc_ms: Option<f64>,
+    // One line per objective that isn't met
+    pub violations: Vec<String>,
+}
+
+impl SloEvaluation {
+    pub fn breached(&self) -> bool {
+        !self.violations.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    Breach,
+    Recovery,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Alert {
+    pub event: AlertKind,
+    pub model: String,
+    pub timestamp: String,
+    pub window_seconds: u64,
+    pub slo_p95_ttfc_ms: f64,
+    pub slo_success_rate: f64,
+    #[serde(flatten)]
+    pub evaluation: SloEvaluation,
+}
+
+// Probe history and alert state for one model
+pub struct ModelMonitor {
+    model: String,
+    outcomes: VecDeque<ProbeOutcome>,
+    breached: bool,
+}
+
+impl ModelMonitor {
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            outcomes: VecDeque::new(),
+            breached: false,
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn record(&mut self, collector: &MetricsCollector) {
+        let at = Instant::now();
+        for metric in collector.streaming_metrics() {
+            self.outcomes.push_back(ProbeOutcome {
+                at,
+                ttfc: Some(metric.time_to_first_chunk.unwrap_or(metric.total_duration)),
+            });
+        }
+        for _ in collector.failures() {
+            self.outcomes.push_back(ProbeOutcome { at, ttfc: None });
+        }
+    }
+
+    pub fn evaluate(&mut self, slo: &SloConfig) -> SloEvaluation {
+        while self.outcomes.front().is_some_and(|o| o.at.elapsed() > slo.window) {
+            self.outcomes.pop_front();
+        }
+
+        let mut ttfcs: Vec<Duration> = self.outcomes.iter().filter_map(|o| o.ttfc).collect();
+        ttfcs.sort();
+        let requests = self.outcomes.len();
+        let success_rate = if requests == 0 { 100.0 } else { ttfcs.len() as f64 / requests as f64 * 100.0 };
+        let p95_ttfc = (!ttfcs.is_empty()).then(|| percentile(&ttfcs, 95));
+
+        let mut violations = Vec::new();
+        if let Some(p95) = p95_ttfc.filter(|&p95| p95 >= slo.max_p95_ttfc) {
+            violations.push(format!("p95 TTFC {:.0?} >= {:.0?}", p95, slo.max_p95_ttfc));
+        }
+        if requests > 0 && success_rate <= slo.min_success_rate {
+            violations.push(format!("success rate {:.2}% <= {:.2}%", success_rate, slo.min_success_rate));
+        }
+
+        SloEvaluation {
+            requests,
+            success_rate,
+            p95_ttfc_ms: p95_ttfc.map(|d| d.as_secs_f64() * 1000.0),
+            violations,
+        }
+    }
+
+    // An alert when the evaluation changes the breach state, i.e. on breach and on recovery
+    pub fn transition(&mut self, slo: &SloConfig, evaluation: &SloEvaluation) -> Option<Alert> {
+        if evaluation.breached() == self.breached {
+            return None;
+        }
+        self.breached = evaluation.breached();
+
+        Some(Alert {
+            event: if self.breached { AlertKind::Breach } else { AlertKind::Recovery },
+            model: self.model.clone(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            window_seconds: slo.window.as_secs(),
+            slo_p95_ttfc_ms: slo.max_p95_ttfc.as_secs_f64() * 1000.0,
+            slo_success_rate: slo.min_success_rate,
+            evaluation: evaluation.clone(),
+        })
+    }
+}
+
+pub async fn send_webhook(client: &reqwest::Client, url: &str, alert: &Alert) -> Result<()> {
+    let response = client.post(url).json(alert).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Webhook returned {}", response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slo() -> SloConfig {
+        SloConfig {
+            window: Duration::from_secs(60),
+            max_p95_ttfc: Duration::from_millis(500),
+            min_success_rate: 90.0,
+        }
+    }
+
+    fn monitor_with(outcomes: &[Option<u64>]) -> ModelMonitor {
+        let mut monitor = ModelMonitor::new("gpt-4o");
+        for &ttfc in outcomes {
+            monitor.outcomes.push_back(ProbeOutcome {
+                at: Instant::now(),
+                ttfc: ttfc.map(Duration::from_millis),
+            });
+        }
+        monitor
+    }
+
+    #[test]
+    fn healthy_window_meets_the_slo() {
+        let evaluation = monitor_with(&[Some(100); 20]).evaluate(&slo());
+        assert_eq!(evaluation.requests, 20);
+        assert_eq!(evaluation.success_rate, 100.0);
+        assert_eq!(evaluation.p95_ttfc_ms, Some(100.0));
+        assert!(!evaluation.breached());
+    }
+
+    #[test]
+    fn slow_tail_and_failures_are_violations() {
+        let mut outcomes = vec![Some(100); 17];
+        outcomes.extend([Some(900), None, None]);
+        let evaluation = monitor_with(&outcomes).evaluate(&slo());
+        assert_eq!(evaluation.p95_ttfc_ms, Some(900.0));
+        assert_eq!(evaluation.success_rate, 90.0);
+        assert_eq!(evaluation.violations.len(), 2);
+    }
+
+    #[test]
+    fn outcomes_outside_the_window_are_dropped() {
+        let mut monitor = monitor_with(&[Some(100)]);
+        monitor.outcomes.push_front(ProbeOutcome {
+            at: Instant::now().checked_sub(Duration::from_secs(120)).unwrap(),
+            ttfc: None,
+        });
+        let evaluation = monitor.evaluate(&slo());
+        assert_eq!(evaluation.requests, 1);
+        assert!(!evaluation.breached());
+    }
+
+    #[test]
+    fn alerts_only_when_the_breach_state_changes() {
+        let slo = slo();
+        let mut monitor = monitor_with(&[None]);
+        let breach = monitor.evaluate(&slo);
+
+        let alert = monitor.transition(&slo, &breach).unwrap();
+        assert_eq!(alert.event, AlertKind::Breach);
+        assert_eq!(alert.slo_p95_ttfc_ms, 500.0);
+        assert!(monitor.transition(&slo, &breach).is_none());
+
+        monitor.outcomes.clear();
+        let recovered = monitor.evaluate(&slo);
+        assert_eq!(monitor.transition(&slo, &recovered).unwrap().event, AlertKind::Recovery);
+        assert!(monitor.transition(&slo, &recovered).is_none());
+    }
+}
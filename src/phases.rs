@@ -0,0 +1,349 @@
+use bytes::Bytes;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::client::{ClientSessionMemoryCache, ClientSessionStore, Tls12ClientSessionValue};
+use rustls::pki_types::ServerName;
+use rustls::{NamedGroup, RootCertStore};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use crate::metrics::{mean_duration, percentile};
+
+// Connection phase timing. reqwest doesn't expose connection events, so they are observed
+// from hooks it does accept: a DNS resolver, a layer around the connector (TCP + TLS),
+// the TLS session store (read by rustls when it builds the ClientHello, i.e. right after
+// TCP connects) and the request body stream. Hooks find the request they belong to via a
+// task-local recorder set by the client around `send()`.
+
+tokio::task_local! {
+    static RECORDER: Arc<PhaseRecorder>;
+}
+
+fn mark(set: impl FnOnce(&mut Marks, Instant)) {
+    let _ = RECORDER.try_with(|recorder| recorder.mark(set));
+}
+
+#[derive(Debug, Default)]
+struct Marks {
+    connect_start: Option<Instant>,
+    dns_start: Option<Instant>,
+    dns_end: Option<Instant>,
+    tls_start: Option<Instant>,
+    connect_end: Option<Instant>,
+    request_written: Option<Instant>,
+}
+
+pub struct PhaseRecorder {
+    start: Instant,
+    marks: Mutex<Marks>,
+}
+
+impl PhaseRecorder {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            start: Instant::now(),
+            marks: Mutex::new(Marks::default()),
+        })
+    }
+
+    fn mark(&self, set: impl FnOnce(&mut Marks, Instant)) {
+        set(&mut self.marks.lock().unwrap(), Instant::now());
+    }
+
+    // Runs `future` (the `send()` of one request) with this recorder receiving its events
+    pub async fn scope<F: Future>(self: &Arc<Self>, future: F) -> F::Output {
+        RECORDER.scope(Arc::clone(self), future).await
+    }
+
+    // Wraps a request body so the time it's handed to the connection is recorded. The body
+    // is polled by hyper's connection task, so the recorder is captured here rather than
+    // looked up from the task-local.
+    pub fn timed_body(self: &Arc<Self>, body: Vec<u8>) -> reqwest::Body {
+        let recorder = Arc::clone(self);
+        let stream = futures::stream::unfold(Some(body), move |body| {
+            let recorder = Arc::clone(&recorder);
+            async move {
+                // Marked when the chunk is taken and again if hyper polls for the end
+                recorder.mark(|m, now| m.request_written = Some(now));
+                body.map(|body| (Ok::<_, std::io::Error>(Bytes::from(body)), None))
+            }
+        });
+        reqwest::Body::wrap_stream(stream)
+    }
+
+    // `headers` is when the response headers arrived, `end` when the body was fully read.
+    pub fn finish(&self, headers: Instant, end: Instant) -> PhaseTimings {
+        let marks = self.marks.lock().unwrap();
+        let since = |from: Option<Instant>, to: Option<Instant>| match (from, to) {
+            (Some(from), Some(to)) => Some(to.saturating_duration_since(from)),
+            _ => None,
+        };
+
+        // A connection counts as this request's only if it was ready before the body went
+        // out; otherwise the request was served by a pooled connection while a racing
+        // connect finished in the background.
+        let written = marks.request_written.unwrap_or(headers);
+        let connected = marks.connect_end.filter(|&end| end <= written);
+
+        let (dns, tcp_connect, tls_handshake) = match connected {
+            Some(connect_end) => {
+                let tcp_start = marks.dns_end.or(marks.connect_start);
+                let tcp_end = marks.tls_start.unwrap_or(connect_end);
+                (
+                    since(marks.dns_start, marks.dns_end),
+                    since(tcp_start, Some(tcp_end)),
+                    since(marks.tls_start, Some(connect_end)),
+                )
+            }
+            None => (None, None, None),
+        };
+
+        let ready = connected.unwrap_or(self.start);
+        PhaseTimings {
+            reused_connection: connected.is_none(),
+            dns,
+            tcp_connect,
+            tls_handshake,
+            request_write: written.saturating_duration_since(ready),
+            server_wait: headers.saturating_duration_since(written),
+            body_download: end.saturating_duration_since(headers),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PhaseTimings {
+    pub reused_connection: bool,
+    // None on reused connections (and for DNS when the host is an IP literal)
+    pub dns: Option<Duration>,
+    pub tcp_connect: Option<Duration>,
+    pub tls_handshake: Option<Duration>,
+    // Includes waiting for a pooled connection when one was reused
+    pub request_write: Duration,
+    pub server_wait: Duration,
+    pub body_download: Duration,
+}
+
+pub struct TimingResolver;
+
+impl Resolve for TimingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let recorder = RECORDER.try_with(Arc::clone).ok();
+        Box::pin(async move {
+            if let Some(recorder) = &recorder {
+                recorder.mark(|m, now| m.dns_start = Some(now));
+            }
+            let addrs: Vec<_> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if let Some(recorder) = &recorder {
+                recorder.mark(|m, now| m.dns_end = Some(now));
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+// Tower layer around reqwest's connector, timing the whole connect (DNS + TCP + TLS)
+#[derive(Clone)]
+pub struct ConnectTimingLayer;
+
+impl<S> tower_layer::Layer<S> for ConnectTimingLayer {
+    type Service = ConnectTiming<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectTiming { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConnectTiming<S> {
+    inner: S,
+}
+
+impl<S, R> tower_service::Service<R> for ConnectTiming<S>
+where
+    S: tower_service::Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        mark(|m, now| m.connect_start = Some(now));
+        let recorder = RECORDER.try_with(Arc::clone).ok();
+        let connecting = self.inner.call(request);
+        Box::pin(async move {
+            let result = connecting.await;
+            if let (Some(recorder), Ok(_)) = (&recorder, &result) {
+                recorder.mark(|m, now| m.connect_end = Some(now));
+            }
+            result
+        })
+    }
+}
+
+// Session store that marks the TLS handshake start: rustls consults it while building the
+// ClientHello. Storage is delegated to the default in-memory cache.
+#[derive(Debug)]
+struct TimingSessionStore {
+    inner: ClientSessionMemoryCache,
+}
+
+impl TimingSessionStore {
+    fn handshake_started(&self) {
+        mark(|m, now| {
+            m.tls_start.get_or_insert(now);
+        });
+    }
+}
+
+impl ClientSessionStore for TimingSessionStore {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.inner.set_kx_hint(server_name, group)
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.handshake_started();
+        self.inner.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.inner.set_tls12_session(server_name, value)
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.handshake_started();
+        self.inner.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.inner.remove_tls12_session(server_name)
+    }
+
+    fn insert_tls13_ticket(&self, server_name: ServerName<'static>, value: rustls::client::Tls13ClientSessionValue) {
+        self.inner.insert_tls13_ticket(server_name, value)
+    }
+
+    fn take_tls13_ticket(&self, server_name: &ServerName<'static>) -> Option<rustls::client::Tls13ClientSessionValue> {
+        self.handshake_started();
+        self.inner.take_tls13_ticket(server_name)
+    }
+}
+
+// Equivalent of reqwest's own rustls setup (ring, webpki roots) with the ALPN protocols
+// for the chosen HTTP version and the timing session store installed.
+pub fn tls_config(alpn_protocols: Vec<Vec<u8>>) -> anyhow::Result<rustls::ClientConfig> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let mut config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = alpn_protocols;
+    config.resumption = rustls::client::Resumption::store(Arc::new(TimingSessionStore {
+        inner: ClientSessionMemoryCache::new(256),
+    }));
+    Ok(config)
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PhaseSummary {
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p99: Duration,
+}
+
+fn summarize_phase(mut values: Vec<Duration>) -> Option<PhaseSummary> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    Some(PhaseSummary {
+        mean: mean_duration(&values),
+        p50: percentile(&values, 50),
+        p99: percentile(&values, 99),
+    })
+}
+
+// Mean time per phase for a group of requests
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PhaseBreakdown {
+    pub requests: usize,
+    pub dns: Duration,
+    pub tcp_connect: Duration,
+    pub tls_handshake: Duration,
+    pub request_write: Duration,
+    pub server_wait: Duration,
+    pub body_download: Duration,
+}
+
+fn breakdown(timings: &[&PhaseTimings]) -> PhaseBreakdown {
+    let n = timings.len().max(1) as u32;
+    let mean = |f: &dyn Fn(&PhaseTimings) -> Duration| timings.iter().map(|t| f(t)).sum::<Duration>() / n;
+    PhaseBreakdown {
+        requests: timings.len(),
+        dns: mean(&|t| t.dns.unwrap_or_default()),
+        tcp_connect: mean(&|t| t.tcp_connect.unwrap_or_default()),
+        tls_handshake: mean(&|t| t.tls_handshake.unwrap_or_default()),
+        request_write: mean(&|t| t.request_write),
+        server_wait: mean(&|t| t.server_wait),
+        body_download: mean(&|t| t.body_download),
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+pub struct PhaseStats {
+    pub new_connections: usize,
+    pub reused_connections: usize,
+    // Connection phases cover new connections only
+    pub dns: Option<PhaseSummary>,
+    pub tcp_connect: Option<PhaseSummary>,
+    pub tls_handshake: Option<PhaseSummary>,
+    pub request_write: Option<PhaseSummary>,
+    pub server_wait: Option<PhaseSummary>,
+    pub body_download: Option<PhaseSummary>,
+    // Where the time went for requests at or above the p99 total duration, to tell
+    // network from backend in the tail
+    pub p99_tail: PhaseBreakdown,
+}
+
+// `samples` pairs each request's total duration with its phases
+pub fn summarize(samples: &[(Duration, PhaseTimings)]) -> Option<PhaseStats> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let mut totals: Vec<Duration> = samples.iter().map(|(total, _)| *total).collect();
+    totals.sort();
+    let p99_total = percentile(&totals, 99);
+    let tail: Vec<&PhaseTimings> = samples
+        .iter()
+        .filter(|(total, _)| *total >= p99_total)
+        .map(|(_, phases)| phases)
+        .collect();
+
+    let collect = |f: &dyn Fn(&PhaseTimings) -> Option<Duration>| samples.iter().filter_map(|(_, t)| f(t)).collect();
+    let new_connections = samples.iter().filter(|(_, t)| !t.reused_connection).count();
+
+    Some(PhaseStats {
+        new_connections,
+        reused_connections: samples.len() - new_connections,
+        dns: summarize_phase(collect(&|t| t.dns)),
+        tcp_connect: summarize_phase(collect(&|t| t.tcp_connect)),
+        tls_handshake: summarize_phase(collect(&|t| t.tls_handshake)),
+        request_write: summarize_phase(collect(&|t| Some(t.request_write))),
+        server_wait: summarize_phase(collect(&|t| Some(t.server_wait))),
+        body_download: summarize_phase(collect(&|t| Some(t.body_download))),
+        p99_tail: breakdown(&tail),
+    })
+}
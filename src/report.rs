@@ -0,0 +1,419 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::metrics::{self, ms, MetricsCollector};
+
+const PALETTE: &[&str] = &["#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7", "#9c755f", "#bab0ac"];
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 320.0;
+// Left, right, top and bottom space for axis labels
+const MARGIN: (f64, f64, f64, f64) = (60.0, 20.0, 20.0, 40.0);
+const HISTOGRAM_BINS: usize = 20;
+
+// What the report command collected for one model: streaming latency requests and
+// single-request throughput tests
+pub struct ModelReport {
+    pub model: String,
+    pub latency: MetricsCollector,
+    pub throughput: MetricsCollector,
+}
+
+// Per-request values the charts and tables are drawn from, in milliseconds
+struct Series<'a> {
+    model: &'a str,
+    color: &'static str,
+    latencies: Vec<f64>,
+    ttfcs: Vec<f64>,
+    tokens_per_second: Vec<f64>,
+    succeeded: usize,
+    failures: BTreeMap<&'a str, usize>,
+}
+
+impl<'a> Series<'a> {
+    fn new(report: &'a ModelReport, color: &'static str) -> Self {
+        let streaming = report.latency.streaming_metrics();
+        let mut latencies: Vec<f64> = streaming.iter().map(|m| ms(m.total_duration)).collect();
+        latencies.sort_by(f64::total_cmp);
+        let mut ttfcs: Vec<f64> = streaming.iter().filter_map(|m| m.time_to_first_chunk).map(ms).collect();
+        ttfcs.sort_by(f64::total_cmp);
+        let throughput: Vec<_> =
+            report.throughput.throughput_metrics().iter().filter(|m| m.successful_requests > 0).collect();
+
+        let mut failures = BTreeMap::new();
+        for label in report.latency.failures().iter().chain(report.throughput.failures()) {
+            *failures.entry(label.as_str()).or_insert(0) += 1;
+        }
+
+        Self {
+            model: &report.model,
+            color,
+            latencies,
+            ttfcs,
+            tokens_per_second: throughput.iter().map(|m| m.tokens_per_second).collect(),
+            succeeded: streaming.len() + throughput.len(),
+            failures,
+        }
+    }
+
+    fn failed(&self) -> usize {
+        self.failures.values().sum()
+    }
+
+    fn success_rate(&self) -> Option<f64> {
+        let total = self.succeeded + self.failed();
+        (total > 0).then(|| self.succeeded as f64 / total as f64 * 100.0)
+    }
+}
+
+// The shared helpers, but None for an empty series so the table shows "-"
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| metrics::mean_f64(values))
+}
+
+fn percentile(sorted: &[f64], percent: usize) -> Option<f64> {
+    (!sorted.is_empty()).then(|| metrics::percentile(sorted, percent))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// A round step giving roughly `target` ticks between 0 and `max`
+fn tick_step(max: f64, target: usize) -> f64 {
+    let raw = max / target as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0].iter().map(|f| f * magnitude).find(|&s| s >= raw).unwrap_or(10.0 * magnitude)
+}
+
+// Rounds the axis maximum up to a whole tick
+fn axis_max(max: f64) -> (f64, f64) {
+    let max = if max > 0.0 { max } else { 1.0 };
+    let step = tick_step(max, 6);
+    ((max / step).ceil() * step, step)
+}
+
+// Generates one self-contained HTML page: inline CSS, inline SVG charts and a few lines of
+// script for sorting tables. Nothing is loaded from the network.
+pub fn render(title: &str, generated_at: &str, models: &[ModelReport]) -> String {
+    let series: Vec<Series> = models
+        .iter()
+        .enumerate()
+        .map(|(i, report)| Series::new(report, PALETTE[i % PALETTE.len()]))
+        .collect();
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2rem auto; max-width: 960px; color: #222; }}
+h1 {{ margin-bottom: 0.2rem; }}
+.meta {{ color: #666; margin-top: 0; }}
+table {{ border-collapse: collapse; width: 100%; margin: 1rem 0; font-size: 0.9rem; }}
+th, td {{ padding: 0.4rem 0.6rem; border-bottom: 1px solid #ddd; text-align: right; }}
+th:first-child, td:first-child {{ text-align: left; }}
+th.sortable {{ cursor: pointer; user-select: none; background: #f5f5f5; }}
+th.sortable:hover {{ background: #e8e8e8; }}
+th[data-dir="asc"]::after {{ content: " ▲"; }}
+th[data-dir="desc"]::after {{ content: " ▼"; }}
+.swatch {{ display: inline-block; width: 0.8rem; height: 0.8rem; margin-right: 0.4rem; vertical-align: middle; }}
+.histograms {{ display: flex; flex-wrap: wrap; gap: 1rem; }}
+.histograms figure {{ margin: 0; }}
+figcaption {{ font-size: 0.85rem; text-align: center; }}
+svg text {{ font-size: 11px; fill: #444; }}
+.empty {{ color: #888; font-style: italic; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class="meta">Generated {generated_at} · {count} models</p>
+"#,
+        title = escape(title),
+        generated_at = escape(generated_at),
+        count = models.len(),
+    );
+
+    summary_table(&mut html, &series);
+    latency_cdf(&mut html, &series);
+    ttfc_histograms(&mut html, &series);
+    tps_bars(&mut html, &series);
+    error_breakdown(&mut html, &series);
+
+    html.push_str(SORT_SCRIPT);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn cell(value: Option<f64>, decimals: usize) -> String {
+    match value {
+        Some(v) => format!(r#"<td data-value="{v}">{v:.decimals$}</td>"#),
+        None => r#"<td data-value="">-</td>"#.to_string(),
+    }
+}
+
+fn summary_table(html: &mut String, series: &[Series]) {
+    html.push_str("<h2>Summary</h2>\n<p>Click a column header to sort. Latencies are streaming requests, end to end; tokens/s comes from the throughput test.</p>\n");
+    html.push_str(r#"<table class="sortable"><thead><tr>"#);
+    for header in [
+        "Model", "Requests", "Success %", "Mean latency (ms)", "P50 (ms)", "P95 (ms)", "P99 (ms)",
+        "Mean TTFC (ms)", "P95 TTFC (ms)", "Mean tokens/s", "Failed",
+    ] {
+        let _ = write!(html, r#"<th class="sortable">{}</th>"#, header);
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for s in series {
+        let _ = write!(
+            html,
+            r#"<tr><td data-value="{model}"><span class="swatch" style="background:{color}"></span>{model}</td>"#,
+            model = escape(s.model),
+            color = s.color
+        );
+        html.push_str(&cell(Some((s.succeeded + s.failed()) as f64), 0));
+        html.push_str(&cell(s.success_rate(), 1));
+        html.push_str(&cell(mean(&s.latencies), 1));
+        html.push_str(&cell(percentile(&s.latencies, 50), 1));
+        html.push_str(&cell(percentile(&s.latencies, 95), 1));
+        html.push_str(&cell(percentile(&s.latencies, 99), 1));
+        html.push_str(&cell(mean(&s.ttfcs), 1));
+        html.push_str(&cell(percentile(&s.ttfcs, 95), 1));
+        html.push_str(&cell(mean(&s.tokens_per_second), 1));
+        html.push_str(&cell(Some(s.failed() as f64), 0));
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody></table>\n");
+}
+
+fn tick_label(value: f64) -> String {
+    let label = format!("{:.2}", value);
+    label.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// Vertical gridlines with labels under the plot area
+fn x_ticks(html: &mut String, left: f64, plot_width: f64, top: f64, baseline: f64, x_max: f64, x_step: f64) {
+    for i in 0..=(x_max / x_step).round() as usize {
+        let x = i as f64 * x_step;
+        let px = left + x / x_max * plot_width;
+        let _ = write!(
+            html,
+            r##"<line x1="{px:.1}" y1="{top}" x2="{px:.1}" y2="{baseline}" stroke="#eee"/><text x="{px:.1}" y="{ty}" text-anchor="middle">{label}</text>"##,
+            ty = baseline + 15.0,
+            label = tick_label(x),
+        );
+    }
+}
+
+// Axes with gridlines for a chart whose x runs 0..x_max; y labels are supplied by the caller
+fn axes(html: &mut String, x_max: f64, x_step: f64, x_label: &str) {
+    let (left, right, top, bottom) = MARGIN;
+    let plot_width = CHART_WIDTH - left - right;
+    x_ticks(html, left, plot_width, top, CHART_HEIGHT - bottom, x_max, x_step);
+    let _ = write!(
+        html,
+        r##"<line x1="{left}" y1="{y}" x2="{x2}" y2="{y}" stroke="#888"/><text x="{cx}" y="{ly}" text-anchor="middle">{x_label}</text>"##,
+        y = CHART_HEIGHT - bottom,
+        x2 = CHART_WIDTH - right,
+        cx = left + plot_width / 2.0,
+        ly = CHART_HEIGHT - 5.0,
+    );
+}
+
+fn legend(html: &mut String, series: &[Series]) {
+    html.push_str("<p>");
+    for s in series {
+        let _ = write!(html, r#"<span class="swatch" style="background:{}"></span>{} &nbsp; "#, s.color, escape(s.model));
+    }
+    html.push_str("</p>\n");
+}
+
+fn latency_cdf(html: &mut String, series: &[Series]) {
+    html.push_str("<h2>Latency CDF</h2>\n<p>Share of streaming requests completed within a given end-to-end latency.</p>\n");
+    let max = series.iter().filter_map(|s| s.latencies.last()).fold(0.0, |a: f64, &b| a.max(b));
+    if max == 0.0 {
+        html.push_str("<p class=\"empty\">No successful requests</p>\n");
+        return;
+    }
+
+    let (left, right, top, bottom) = MARGIN;
+    let (plot_width, plot_height) = (CHART_WIDTH - left - right, CHART_HEIGHT - top - bottom);
+    let (x_max, x_step) = axis_max(max);
+    let _ = write!(html, r#"<svg width="{CHART_WIDTH}" height="{CHART_HEIGHT}" role="img">"#);
+    axes(html, x_max, x_step, "latency (ms)");
+    for percent in (0..=100).step_by(25) {
+        let py = top + plot_height * (1.0 - percent as f64 / 100.0);
+        let _ = write!(
+            html,
+            r##"<line x1="{left}" y1="{py:.1}" x2="{x2}" y2="{py:.1}" stroke="#eee"/><text x="{tx}" y="{ty:.1}" text-anchor="end">{percent}%</text>"##,
+            x2 = CHART_WIDTH - right,
+            tx = left - 6.0,
+            ty = py + 4.0,
+        );
+    }
+    for s in series.iter().filter(|s| !s.latencies.is_empty()) {
+        // A step function: each request raises the curve by 1/n at its latency
+        let n = s.latencies.len() as f64;
+        let mut points = format!("{left:.1},{:.1}", top + plot_height);
+        for (i, latency) in s.latencies.iter().enumerate() {
+            let px = left + latency / x_max * plot_width;
+            let _ = write!(
+                points,
+                " {px:.1},{:.1} {px:.1},{:.1}",
+                top + plot_height * (1.0 - i as f64 / n),
+                top + plot_height * (1.0 - (i + 1) as f64 / n)
+            );
+        }
+        let _ = write!(
+            html,
+            r#"<polyline fill="none" stroke="{}" stroke-width="2" points="{}"><title>{}</title></polyline>"#,
+            s.color,
+            points,
+            escape(s.model)
+        );
+    }
+    html.push_str("</svg>\n");
+    legend(html, series);
+}
+
+fn ttfc_histograms(html: &mut String, series: &[Series]) {
+    html.push_str("<h2>Time to First Chunk</h2>\n<p>Distribution of TTFC per model, on a shared axis.</p>\n");
+    let max = series.iter().filter_map(|s| s.ttfcs.last()).fold(0.0, |a: f64, &b| a.max(b));
+    if max == 0.0 {
+        html.push_str("<p class=\"empty\">No streamed chunks</p>\n");
+        return;
+    }
+
+    let (x_max, _) = axis_max(max);
+    let bin_width = x_max / HISTOGRAM_BINS as f64;
+    let (width, height, label_space) = (320.0, 160.0, 20.0);
+    html.push_str("<div class=\"histograms\">\n");
+    for s in series.iter().filter(|s| !s.ttfcs.is_empty()) {
+        let mut bins = [0usize; HISTOGRAM_BINS];
+        for ttfc in &s.ttfcs {
+            bins[((ttfc / bin_width) as usize).min(HISTOGRAM_BINS - 1)] += 1;
+        }
+        let tallest = *bins.iter().max().unwrap_or(&1) as f64;
+        let plot_height = height - label_space;
+        let bar_width = width / HISTOGRAM_BINS as f64;
+
+        let _ = write!(html, r#"<figure><svg width="{width}" height="{height}" role="img">"#);
+        for (i, &count) in bins.iter().enumerate().filter(|(_, &c)| c > 0) {
+            let bar_height = count as f64 / tallest * (plot_height - 5.0);
+            let _ = write!(
+                html,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"><title>{:.0}–{:.0} ms: {}</title></rect>"#,
+                i as f64 * bar_width + 1.0,
+                plot_height - bar_height,
+                bar_width - 2.0,
+                bar_height,
+                s.color,
+                i as f64 * bin_width,
+                (i + 1) as f64 * bin_width,
+                count
+            );
+        }
+        let _ = write!(
+            html,
+            r##"<line x1="0" y1="{plot_height}" x2="{width}" y2="{plot_height}" stroke="#888"/><text x="0" y="{ty}">0</text><text x="{width}" y="{ty}" text-anchor="end">{max_label} ms</text></svg><figcaption>{} (n={})</figcaption></figure>"##,
+            escape(s.model),
+            s.ttfcs.len(),
+            ty = height - 5.0,
+            max_label = tick_label(x_max),
+        );
+        html.push('\n');
+    }
+    html.push_str("</div>\n");
+}
+
+fn tps_bars(html: &mut String, series: &[Series]) {
+    html.push_str("<h2>Tokens per Second</h2>\n<p>Mean generation speed after the first chunk in the throughput test.</p>\n");
+    let means: Vec<(&Series, f64)> = series.iter().filter_map(|s| mean(&s.tokens_per_second).map(|m| (s, m))).collect();
+    let max = means.iter().map(|(_, m)| *m).fold(0.0, f64::max);
+    if max == 0.0 {
+        html.push_str("<p class=\"empty\">No successful throughput requests</p>\n");
+        return;
+    }
+
+    let (left, right, top, bottom) = (160.0, 60.0, MARGIN.2, MARGIN.3);
+    let row = 28.0;
+    let height = top + bottom + row * means.len() as f64;
+    let plot_width = CHART_WIDTH - left - right;
+    let (x_max, x_step) = axis_max(max);
+    let _ = write!(html, r#"<svg width="{CHART_WIDTH}" height="{height}" role="img">"#);
+    x_ticks(html, left, plot_width, top, height - bottom, x_max, x_step);
+    for (i, (s, value)) in means.iter().enumerate() {
+        let y = top + i as f64 * row;
+        let bar_width = value / x_max * plot_width;
+        let _ = write!(
+            html,
+            r#"<text x="{tx}" y="{ty:.1}" text-anchor="end">{model}</text><rect x="{left}" y="{ry:.1}" width="{bar_width:.1}" height="{rh}" fill="{color}"/><text x="{vx:.1}" y="{ty:.1}">{value:.1}</text>"#,
+            tx = left - 8.0,
+            ty = y + row / 2.0 + 4.0,
+            model = escape(s.model),
+            ry = y + 4.0,
+            rh = row - 8.0,
+            color = s.color,
+            vx = left + bar_width + 6.0,
+        );
+    }
+    let _ = write!(
+        html,
+        r##"<text x="{cx}" y="{ly}" text-anchor="middle">tokens/s</text></svg>"##,
+        cx = left + plot_width / 2.0,
+        ly = height - 5.0
+    );
+    html.push('\n');
+}
+
+fn error_breakdown(html: &mut String, series: &[Series]) {
+    html.push_str("<h2>Errors</h2>\n");
+    let labels: BTreeSet<&str> = series.iter().flat_map(|s| s.failures.keys().copied()).collect();
+    if labels.is_empty() {
+        html.push_str("<p class=\"empty\">No failed requests</p>\n");
+        return;
+    }
+
+    html.push_str("<p>Failed requests by HTTP status, timeout phase or other error.</p>\n");
+    html.push_str(r#"<table class="sortable"><thead><tr><th class="sortable">Model</th>"#);
+    for label in &labels {
+        let _ = write!(html, r#"<th class="sortable">{}</th>"#, escape(label));
+    }
+    html.push_str(r#"<th class="sortable">Total</th><th class="sortable">Failed %</th></tr></thead>"#);
+    html.push_str("\n<tbody>\n");
+    for s in series {
+        let _ = write!(html, r#"<tr><td data-value="{model}">{model}</td>"#, model = escape(s.model));
+        for label in &labels {
+            html.push_str(&cell(Some(*s.failures.get(label).unwrap_or(&0) as f64), 0));
+        }
+        html.push_str(&cell(Some(s.failed() as f64), 0));
+        html.push_str(&cell(s.success_rate().map(|rate| 100.0 - rate), 1));
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody></table>\n");
+}
+
+// Sorts a table by the clicked column, numerically when every cell has a numeric data-value
+const SORT_SCRIPT: &str = r#"<script>
+document.querySelectorAll("table.sortable").forEach(function (table) {
+  table.querySelectorAll("th").forEach(function (th, column) {
+    th.addEventListener("click", function () {
+      var dir = th.dataset.dir === "asc" ? "desc" : "asc";
+      table.querySelectorAll("th").forEach(function (other) { delete other.dataset.dir; });
+      th.dataset.dir = dir;
+      var body = table.tBodies[0];
+      var rows = Array.prototype.slice.call(body.rows);
+      var value = function (row) { return row.cells[column].dataset.value; };
+      var numeric = rows.every(function (row) { return value(row) === "" || !isNaN(parseFloat(value(row))); });
+      rows.sort(function (a, b) {
+        var x = value(a), y = value(b);
+        var order = numeric ? (x === "" ? Infinity : parseFloat(x)) - (y === "" ? Infinity : parseFloat(y)) : x.localeCompare(y);
+        return dir === "asc" ? order : -order;
+      });
+      rows.forEach(function (row) { body.appendChild(row); });
+    });
+  });
+});
+</script>
+"#;
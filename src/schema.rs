@@ -0,0 +1,177 @@
+use serde_json::Value;
+
+// Validates `value` against the subset of JSON Schema used in tool definitions and
+// structured-output formats: `type`, `enum`, `properties`, `required`,
+// `additionalProperties: false`, `items`, `minItems`/`maxItems` and `minimum`/`maximum`.
+// Returns the first violation found, with a JSON-pointer-style path.
+pub fn validate(value: &Value, schema: &Value) -> Result<(), String> {
+    validate_at(value, schema, "$")
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            return Err(format!("{}: expected {}, got {}", path, types.join(" or "), type_name(value)));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !allowed.contains(value) {
+            return Err(format!("{}: {} is not one of the allowed values", path, value));
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(|m| m.as_f64()) {
+            if n < min {
+                return Err(format!("{}: {} is below the minimum {}", path, n, min));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(|m| m.as_f64()) {
+            if n > max {
+                return Err(format!("{}: {} is above the maximum {}", path, n, max));
+            }
+        }
+    }
+
+    if let Value::Object(object) = value {
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            if let Some(missing) = required
+                .iter()
+                .filter_map(|name| name.as_str())
+                .find(|name| !object.contains_key(*name))
+            {
+                return Err(format!("{}: missing required property \"{}\"", path, missing));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        let extras_allowed = schema.get("additionalProperties") != Some(&Value::Bool(false));
+
+        for (name, property_value) in object {
+            let property_path = format!("{}.{}", path, name);
+            match properties.and_then(|p| p.get(name)) {
+                Some(property_schema) => validate_at(property_value, property_schema, &property_path)?,
+                None if !extras_allowed => return Err(format!("{}: unexpected property", property_path)),
+                None => {}
+            }
+        }
+    }
+
+    if let Value::Array(items) = value {
+        if let Some(min) = schema.get("minItems").and_then(|m| m.as_u64()) {
+            if (items.len() as u64) < min {
+                return Err(format!("{}: {} items, expected at least {}", path, items.len(), min));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(|m| m.as_u64()) {
+            if items.len() as u64 > max {
+                return Err(format!("{}: {} items, expected at most {}", path, items.len(), max));
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                validate_at(item, item_schema, &format!("{}[{}]", path, i))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        // JSON Schema counts numbers with a zero fractional part, like 3.0, as integers
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        // Unknown types aren't ours to reject
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn weather_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "city": {"type": "string"},
+                "unit": {"type": "string", "enum": ["celsius", "fahrenheit"]},
+                "days": {"type": "integer", "minimum": 1, "maximum": 7},
+                "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 2}
+            },
+            "required": ["city"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn accepts_a_conforming_value() {
+        let value = json!({"city": "Paris", "unit": "celsius", "days": 3, "tags": ["a"]});
+        assert_eq!(validate(&value, &weather_schema()), Ok(()));
+    }
+
+    #[test]
+    fn reports_the_first_violation_with_its_path() {
+        let schema = weather_schema();
+        let cases = [
+            (json!({"unit": "celsius"}), "$: missing required property \"city\""),
+            (json!({"city": 1}), "$.city: expected string, got number"),
+            (json!({"city": "Paris", "unit": "kelvin"}), "$.unit: \"kelvin\" is not one of the allowed values"),
+            (json!({"city": "Paris", "days": 9}), "$.days: 9 is above the maximum 7"),
+            (json!({"city": "Paris", "days": 1.5}), "$.days: expected integer, got number"),
+            (json!({"city": "Paris", "days": 8.0}), "$.days: 8 is above the maximum 7"),
+            (json!({"city": "Paris", "tags": ["a", 2]}), "$.tags[1]: expected string, got number"),
+            (json!({"city": "Paris", "tags": ["a", "b", "c"]}), "$.tags: 3 items, expected at most 2"),
+            (json!({"city": "Paris", "country": "FR"}), "$.country: unexpected property"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(validate(&value, &schema), Err(expected.to_string()), "{}", value);
+        }
+    }
+
+    #[test]
+    fn whole_floats_are_integers() {
+        let schema = json!({"type": "integer"});
+        assert_eq!(validate(&json!(3.0), &schema), Ok(()));
+        assert_eq!(validate(&json!(-2), &schema), Ok(()));
+        assert_eq!(validate(&json!(3.5), &schema), Err("$: expected integer, got number".to_string()));
+    }
+
+    #[test]
+    fn allows_extra_properties_unless_forbidden() {
+        let schema = json!({"type": "object", "properties": {"city": {"type": "string"}}});
+        assert_eq!(validate(&json!({"city": "Paris", "country": "FR"}), &schema), Ok(()));
+    }
+
+    #[test]
+    fn accepts_any_of_several_types() {
+        let schema = json!({"type": ["string", "null"]});
+        assert_eq!(validate(&json!(null), &schema), Ok(()));
+        assert_eq!(validate(&json!(true), &schema), Err("$: expected string or null, got boolean".to_string()));
+    }
+}
@@ -0,0 +1,163 @@
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::metrics::{mean_and_percentile, mean_f64, ms, GoodputStats};
+use crate::models::{ChatCompletionRequest, ResponseFormat, StreamOptions};
+use crate::schema;
+
+const STRUCTURED_MAX_COMPLETION_TOKENS: u32 = 400;
+
+// Support tickets to triage; requests cycle through them
+const TICKETS: &[&str] = &[
+    "Our checkout page has returned HTTP 500 for every customer since this morning's deploy. We're losing sales.",
+    "Could you add a dark mode to the dashboard? My eyes get tired in the evening.",
+    "I was charged twice for my March invoice. Please refund the duplicate payment.",
+    "The CSV export drops rows that contain commas inside quoted fields.",
+    "How do I rotate my API key without downtime for the services using it?",
+];
+
+pub fn ticket_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "summary": { "type": "string" },
+            "category": { "type": "string", "enum": ["bug", "billing", "feature_request", "question"] },
+            "priority": { "type": "string", "enum": ["low", "medium", "high", "urgent"] },
+            "tags": { "type": "array", "items": { "type": "string" } },
+            "estimated_hours": { "type": "number", "minimum": 0 },
+            "customer_visible": { "type": "boolean" }
+        },
+        "required": ["summary", "category", "priority", "tags", "estimated_hours", "customer_visible"],
+        "additionalProperties": false
+    })
+}
+
+// `json_schema` sends the schema as the response format; `json_object` only asks for JSON
+// and describes the schema in the prompt. Both are validated against the same schema.
+pub fn structured_request(model: &str, json_schema: bool, index: usize) -> ChatCompletionRequest {
+    let prompt = format!(
+        "Triage this support ticket. Respond with only a JSON object matching this schema:\n{}\n\nTicket: {}",
+        ticket_schema(),
+        TICKETS[index % TICKETS.len()]
+    );
+    let mut request = ChatCompletionRequest::simple_text_request(model, &prompt, true);
+    request.max_completion_tokens = Some(STRUCTURED_MAX_COMPLETION_TOKENS);
+    request.stream_options = Some(StreamOptions { include_usage: true });
+    request.response_format = Some(if json_schema {
+        ResponseFormat::json_schema("ticket_triage", ticket_schema(), true)
+    } else {
+        ResponseFormat::json_object()
+    });
+    request
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conformance {
+    Valid,
+    InvalidJson(String),
+    SchemaViolation(String),
+}
+
+pub fn check(output: &str) -> Conformance {
+    match serde_json::from_str::<Value>(output.trim()) {
+        Ok(value) => match schema::validate(&value, &ticket_schema()) {
+            Ok(()) => Conformance::Valid,
+            Err(violation) => Conformance::SchemaViolation(violation),
+        },
+        Err(e) => Conformance::InvalidJson(e.to_string()),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructuredSample {
+    pub time_to_first_token: Duration,
+    pub total_duration: Duration,
+    pub output_tokens: u32,
+    pub conformance: Conformance,
+}
+
+#[derive(Debug)]
+pub struct StructuredStats {
+    pub model: String,
+    pub requests: usize,
+    pub failed_requests: usize,
+    pub conformance_rate: f64,
+    pub invalid_json: usize,
+    pub schema_violations: usize,
+    // Up to three distinct failure reasons, for the report
+    pub example_failures: Vec<String>,
+    pub mean_ttft: Duration,
+    pub p95_ttft: Duration,
+    pub mean_total_duration: Duration,
+    pub p95_total_duration: Duration,
+    pub mean_tokens_per_second: f64,
+    pub goodput: Option<GoodputStats>,
+}
+
+impl StructuredStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("requests", self.requests as f64),
+            ("failed_requests", self.failed_requests as f64),
+            ("conformance_rate", self.conformance_rate),
+            ("invalid_json", self.invalid_json as f64),
+            ("schema_violations", self.schema_violations as f64),
+            ("mean_ttft", ms(self.mean_ttft)),
+            ("p95_ttft", ms(self.p95_ttft)),
+            ("mean_total_duration", ms(self.mean_total_duration)),
+            ("p95_total_duration", ms(self.p95_total_duration)),
+            ("tokens_per_second", self.mean_tokens_per_second),
+        ]
+        .into_iter()
+        .chain(self.goodput.iter().flat_map(GoodputStats::history_metrics))
+        .collect()
+    }
+}
+
+pub fn summarize(model: &str, samples: &[StructuredSample], failed_requests: usize, goodput: Option<GoodputStats>) -> StructuredStats {
+    let (mean_ttft, p95_ttft) = mean_and_percentile(samples.iter().map(|s| s.time_to_first_token).collect(), 95);
+    let (mean_total_duration, p95_total_duration) =
+        mean_and_percentile(samples.iter().map(|s| s.total_duration).collect(), 95);
+
+    let tps: Vec<f64> = samples
+        .iter()
+        .filter(|s| s.total_duration > s.time_to_first_token)
+        .map(|s| s.output_tokens as f64 / (s.total_duration - s.time_to_first_token).as_secs_f64())
+        .collect();
+
+    let mut example_failures: Vec<String> = Vec::new();
+    for sample in samples {
+        let reason = match &sample.conformance {
+            Conformance::Valid => continue,
+            Conformance::InvalidJson(e) => format!("invalid JSON: {}", e),
+            Conformance::SchemaViolation(v) => format!("schema violation: {}", v),
+        };
+        if example_failures.len() < 3 && !example_failures.contains(&reason) {
+            example_failures.push(reason);
+        }
+    }
+
+    let valid = samples.iter().filter(|s| s.conformance == Conformance::Valid).count();
+
+    StructuredStats {
+        model: model.to_string(),
+        requests: samples.len() + failed_requests,
+        failed_requests,
+        conformance_rate: if samples.is_empty() { 0.0 } else { valid as f64 / samples.len() as f64 * 100.0 },
+        invalid_json: samples
+            .iter()
+            .filter(|s| matches!(s.conformance, Conformance::InvalidJson(_)))
+            .count(),
+        schema_violations: samples
+            .iter()
+            .filter(|s| matches!(s.conformance, Conformance::SchemaViolation(_)))
+            .count(),
+        example_failures,
+        mean_ttft,
+        p95_ttft,
+        mean_total_duration,
+        p95_total_duration,
+        mean_tokens_per_second: mean_f64(&tps),
+        goodput,
+    }
+}
@@ -0,0 +1,279 @@
+use rand::Rng;
+use std::time::Duration;
+
+use crate::metrics::{linear_fit, mean_duration, mean_f64, ms, percentile, weighted_mean, GoodputStats, LinearFit};
+use crate::models::ChatMessage;
+use crate::tokenizer::TokenizerRegistry;
+
+const FILLER_WORDS: &[&str] = &[
+    "network", "latency", "river", "mountain", "signal", "garden", "engine", "window", "harbor",
+    "planet", "market", "silver", "forest", "bridge", "letter", "winter", "summer", "pencil",
+    "castle", "rocket", "candle", "island", "mirror", "thunder", "valley", "coffee", "orange",
+    "ladder", "blanket", "compass", "library", "station", "meadow", "anchor", "feather", "lantern",
+    "battery", "cabinet", "dolphin", "glacier", "horizon", "journal", "kitchen", "machine",
+    "notebook", "orchard", "pattern", "quarter", "rainbow", "shelter", "theater", "uniform",
+    "village", "weather", "yogurt", "zephyr", "account", "balance", "capital", "diamond",
+];
+
+const PREFILL_INSTRUCTION: &str = "\n\nIgnore the text above and reply with just: OK";
+
+// Build a prompt of roughly `target_tokens` input tokens for `model`, including chat
+// framing. A random nonce leads the text so provider prompt caches can't reuse a prefix
+// from an earlier request and hide the prefill cost.
+pub fn synthetic_prompt(tokenizers: &TokenizerRegistry, model: &str, target_tokens: u32) -> String {
+    let framing = tokenizers
+        .count_prompt(
+            model,
+            &[ChatMessage::user(PREFILL_INSTRUCTION)],
+        )
+        .0;
+    let filler = synthetic_text(tokenizers, model, target_tokens.saturating_sub(framing) as usize);
+    format!("{}{}", filler, PREFILL_INSTRUCTION)
+}
+
+// Random filler text cut to at most `tokens` tokens (by the model's tokenizer), led by a
+// unique nonce. Usually exactly `tokens`, but decoding a cut token sequence can re-encode a
+// little shorter, and models without a tokenizer are cut by the character heuristic.
+pub fn synthetic_text(tokenizers: &TokenizerRegistry, model: &str, tokens: usize) -> String {
+    let mut rng = rand::rng();
+    let mut filler = format!("[{:016x}]", rng.random::<u64>());
+    // Every filler word is at least one token, so this always overshoots before truncation
+    for _ in 0..tokens {
+        filler.push(' ');
+        filler.push_str(FILLER_WORDS[rng.random_range(0..FILLER_WORDS.len())]);
+    }
+
+    tokenizers.truncate(model, &filler, tokens)
+}
+
+#[derive(Debug, Clone)]
+pub struct PrefillSample {
+    pub target_tokens: u32,
+    // Server-reported prompt tokens when available, otherwise the local count
+    pub input_tokens: u32,
+    pub time_to_first_token: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrefillPoint {
+    pub target_tokens: u32,
+    pub mean_input_tokens: u32,
+    pub successful_requests: usize,
+    pub failed_requests: usize,
+    pub mean_ttft: Duration,
+    pub p50_ttft: Duration,
+}
+
+#[derive(Debug)]
+pub struct PrefillSweepStats {
+    pub model: String,
+    pub context_window: Option<u32>,
+    pub points: Vec<PrefillPoint>,
+    pub skipped_lengths: Vec<u32>,
+    // TTFT (ms) against input length (thousands of tokens)
+    pub fit: Option<LinearFit>,
+    pub goodput: Option<GoodputStats>,
+}
+
+impl PrefillSweepStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        let mut metrics = vec![
+            ("requests", self.points.iter().map(|p| p.successful_requests + p.failed_requests).sum::<usize>() as f64),
+            ("failed_requests", self.points.iter().map(|p| p.failed_requests).sum::<usize>() as f64),
+        ];
+        if let Some(fit) = &self.fit {
+            metrics.push(("ttft_ms_per_1k_tokens", fit.slope));
+            metrics.push(("ttft_intercept", fit.intercept));
+            metrics.push(("fit_r_squared", fit.r_squared));
+        }
+        metrics.extend(self.goodput.iter().flat_map(GoodputStats::history_metrics));
+        metrics
+    }
+}
+
+pub fn summarize_prefill(
+    model: &str,
+    context_window: Option<u32>,
+    lengths: &[u32],
+    skipped_lengths: Vec<u32>,
+    samples: &[PrefillSample],
+    failures: &[u32],
+    goodput: Option<GoodputStats>,
+) -> PrefillSweepStats {
+    let mut points = Vec::new();
+
+    for &target in lengths.iter().filter(|l| !skipped_lengths.contains(l)) {
+        let mut ttfts: Vec<Duration> = samples
+            .iter()
+            .filter(|s| s.target_tokens == target)
+            .map(|s| s.time_to_first_token)
+            .collect();
+        ttfts.sort();
+        let failed_requests = failures.iter().filter(|&&f| f == target).count();
+
+        if ttfts.is_empty() {
+            points.push(PrefillPoint {
+                target_tokens: target,
+                mean_input_tokens: 0,
+                successful_requests: 0,
+                failed_requests,
+                mean_ttft: Duration::from_secs(0),
+                p50_ttft: Duration::from_secs(0),
+            });
+            continue;
+        }
+
+        let inputs: Vec<u32> = samples
+            .iter()
+            .filter(|s| s.target_tokens == target)
+            .map(|s| s.input_tokens)
+            .collect();
+
+        points.push(PrefillPoint {
+            target_tokens: target,
+            mean_input_tokens: inputs.iter().sum::<u32>() / inputs.len() as u32,
+            successful_requests: ttfts.len(),
+            failed_requests,
+            mean_ttft: mean_duration(&ttfts),
+            p50_ttft: percentile(&ttfts, 50),
+        });
+    }
+
+    let fit_points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|s| {
+            (
+                s.input_tokens as f64 / 1000.0,
+                s.time_to_first_token.as_secs_f64() * 1000.0,
+            )
+        })
+        .collect();
+
+    PrefillSweepStats {
+        model: model.to_string(),
+        context_window,
+        points,
+        skipped_lengths,
+        fit: linear_fit(&fit_points),
+        goodput,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodeSample {
+    pub max_tokens: u32,
+    pub output_tokens: u32,
+    pub time_to_first_token: Duration,
+    pub total_duration: Duration,
+    pub finish_reason: Option<String>,
+}
+
+impl DecodeSample {
+    // Anything other than hitting the token cap means the model stopped on its own
+    pub fn stopped_early(&self) -> bool {
+        self.finish_reason.as_deref() != Some("length")
+    }
+
+    fn generation_duration(&self) -> Duration {
+        self.total_duration.saturating_sub(self.time_to_first_token)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodePoint {
+    pub max_tokens: u32,
+    pub successful_requests: usize,
+    pub failed_requests: usize,
+    pub mean_output_tokens: u32,
+    pub mean_tokens_per_second: f64,
+    // Time per output token after the first
+    pub mean_tpot: Duration,
+    pub mean_total_duration: Duration,
+    pub p95_total_duration: Duration,
+    pub stopped_early: usize,
+}
+
+#[derive(Debug)]
+pub struct DecodeSweepStats {
+    pub model: String,
+    pub points: Vec<DecodePoint>,
+    pub goodput: Option<GoodputStats>,
+}
+
+impl DecodeSweepStats {
+    // Decode speed averaged over every output length, weighted by successful requests
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("requests", self.points.iter().map(|p| p.successful_requests + p.failed_requests).sum::<usize>() as f64),
+            ("failed_requests", self.points.iter().map(|p| p.failed_requests).sum::<usize>() as f64),
+            ("tokens_per_second", weighted_mean(self.points.iter().map(|p| (p.mean_tokens_per_second, p.successful_requests)))),
+            ("mean_tpot", weighted_mean(self.points.iter().map(|p| (ms(p.mean_tpot), p.successful_requests)))),
+            ("stopped_early", self.points.iter().map(|p| p.stopped_early).sum::<usize>() as f64),
+        ]
+        .into_iter()
+        .chain(self.goodput.iter().flat_map(GoodputStats::history_metrics))
+        .collect()
+    }
+}
+
+pub fn summarize_decode(
+    model: &str,
+    lengths: &[u32],
+    samples: &[DecodeSample],
+    failures: &[u32],
+    goodput: Option<GoodputStats>,
+) -> DecodeSweepStats {
+    let points = lengths
+        .iter()
+        .map(|&max_tokens| {
+            let point_samples: Vec<&DecodeSample> = samples.iter().filter(|s| s.max_tokens == max_tokens).collect();
+            let failed_requests = failures.iter().filter(|&&f| f == max_tokens).count();
+            let n = point_samples.len();
+
+            if n == 0 {
+                return DecodePoint {
+                    max_tokens,
+                    successful_requests: 0,
+                    failed_requests,
+                    mean_output_tokens: 0,
+                    mean_tokens_per_second: 0.0,
+                    mean_tpot: Duration::from_secs(0),
+                    mean_total_duration: Duration::from_secs(0),
+                    p95_total_duration: Duration::from_secs(0),
+                    stopped_early: 0,
+                };
+            }
+
+            let tps: Vec<f64> = point_samples
+                .iter()
+                .filter(|s| !s.generation_duration().is_zero())
+                .map(|s| s.output_tokens as f64 / s.generation_duration().as_secs_f64())
+                .collect();
+            let tpots: Vec<Duration> = point_samples
+                .iter()
+                .filter(|s| s.output_tokens > 1)
+                .map(|s| s.generation_duration() / (s.output_tokens - 1))
+                .collect();
+            let mut totals: Vec<Duration> = point_samples.iter().map(|s| s.total_duration).collect();
+            totals.sort();
+
+            DecodePoint {
+                max_tokens,
+                successful_requests: n,
+                failed_requests,
+                mean_output_tokens: point_samples.iter().map(|s| s.output_tokens).sum::<u32>() / n as u32,
+                mean_tokens_per_second: mean_f64(&tps),
+                mean_tpot: mean_duration(&tpots),
+                mean_total_duration: mean_duration(&totals),
+                p95_total_duration: percentile(&totals, 95),
+                stopped_early: point_samples.iter().filter(|s| s.stopped_early()).count(),
+            }
+        })
+        .collect();
+
+    DecodeSweepStats {
+        model: model.to_string(),
+        points,
+        goodput,
+    }
+}
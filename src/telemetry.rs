@@ -0,0 +1,73 @@
+use anyhow::Result;
+use opentelemetry::propagation::{Injector, TextMapPropagator};
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::filter::{EnvFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+// Target of the per-request spans and their events. They're debug level so the console
+// log stays as it was; only the OTLP layer subscribes to them.
+pub const REQUEST_TARGET: &str = "sudo_request";
+
+const SERVICE_NAME: &str = "sudo-benchmarks";
+
+// Installs the console log and, with an endpoint, an OTLP/HTTP exporter for request spans.
+// The returned provider must be passed to `shutdown` so buffered spans are flushed.
+pub async fn init(otlp_endpoint: Option<String>) -> Result<Option<SdkTracerProvider>> {
+    let console = tracing_subscriber::fmt::layer().with_filter(EnvFilter::new("info"));
+
+    let Some(endpoint) = otlp_endpoint else {
+        tracing_subscriber::registry().with(console).init();
+        return Ok(None);
+    };
+
+    // The exporter's blocking HTTP client can't be created (or later dropped) on an async
+    // worker thread
+    let provider = tokio::task::spawn_blocking(move || -> Result<SdkTracerProvider> {
+        let exporter = SpanExporter::builder().with_http().with_endpoint(endpoint).build()?;
+        Ok(SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+            .build())
+    })
+    .await??;
+
+    let spans = tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer(SERVICE_NAME))
+        .with_filter(Targets::new().with_target(REQUEST_TARGET, tracing::Level::DEBUG));
+    tracing_subscriber::registry().with(console).with(spans).init();
+    Ok(Some(provider))
+}
+
+pub async fn shutdown(provider: Option<SdkTracerProvider>) -> Result<()> {
+    if let Some(provider) = provider {
+        tokio::task::spawn_blocking(move || provider.shutdown()).await??;
+    }
+    Ok(())
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(&value)) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+// W3C `traceparent` for the current span, so gateway traces join the client's. Empty
+// when spans aren't exported.
+pub fn trace_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let context = tracing::Span::current().context();
+    TraceContextPropagator::new().inject_context(&context, &mut HeaderInjector(&mut headers));
+    headers
+}
@@ -0,0 +1,227 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tiktoken_rs::CoreBPE;
+
+use crate::models::{base_model_name, ChatMessage};
+
+// Where a token count came from, so reports can show how trustworthy a TPS number is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenSource {
+    Usage,
+    Tokenizer,
+    Heuristic,
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Usage => write!(f, "server usage"),
+            TokenSource::Tokenizer => write!(f, "tokenizer"),
+            TokenSource::Heuristic => write!(f, "heuristic"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenSourceCounts {
+    pub usage: usize,
+    pub tokenizer: usize,
+    pub heuristic: usize,
+}
+
+impl TokenSourceCounts {
+    pub fn record(&mut self, source: TokenSource) {
+        match source {
+            TokenSource::Usage => self.usage += 1,
+            TokenSource::Tokenizer => self.tokenizer += 1,
+            TokenSource::Heuristic => self.heuristic += 1,
+        }
+    }
+}
+
+impl fmt::Display for TokenSourceCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} server usage, {} tokenizer, {} heuristic",
+            self.usage, self.tokenizer, self.heuristic
+        )
+    }
+}
+
+pub enum Tokenizer {
+    // tiktoken-compatible BPE used by the OpenAI model families
+    Bpe(&'static CoreBPE),
+    // Local Hugging Face `tokenizer.json`
+    HuggingFace(Box<tokenizers::Tokenizer>),
+}
+
+impl Tokenizer {
+    // None when the text can't be encoded, so callers can fall back to (and label) an estimate
+    pub fn count(&self, text: &str) -> Option<u32> {
+        match self {
+            Tokenizer::Bpe(bpe) => Some(bpe.encode_with_special_tokens(text).len() as u32),
+            Tokenizer::HuggingFace(tokenizer) => tokenizer
+                .encode(text, false)
+                .ok()
+                .map(|encoding| encoding.get_ids().len() as u32),
+        }
+    }
+
+    // Cut `text` down to at most `max_tokens` tokens.
+    pub fn truncate(&self, text: &str, max_tokens: usize) -> String {
+        match self {
+            Tokenizer::Bpe(bpe) => {
+                let tokens = bpe.encode_with_special_tokens(text);
+                if tokens.len() <= max_tokens {
+                    return text.to_string();
+                }
+                bpe.decode(tokens[..max_tokens].to_vec())
+                    .unwrap_or_else(|_| truncate_chars(text, max_tokens))
+            }
+            Tokenizer::HuggingFace(tokenizer) => tokenizer
+                .encode(text, false)
+                .ok()
+                .and_then(|encoding| {
+                    let ids = encoding.get_ids();
+                    tokenizer.decode(&ids[..ids.len().min(max_tokens)], false).ok()
+                })
+                .unwrap_or_else(|| truncate_chars(text, max_tokens)),
+        }
+    }
+
+    fn from_spec(spec: &str) -> Result<Self> {
+        if let Some(bpe) = bpe_for_encoding(spec) {
+            return Ok(Tokenizer::Bpe(bpe));
+        }
+        let tokenizer = tokenizers::Tokenizer::from_file(spec)
+            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer from '{}': {}", spec, e))?;
+        Ok(Tokenizer::HuggingFace(Box::new(tokenizer)))
+    }
+}
+
+// Rough token estimation: ~4 characters per token
+pub fn heuristic_token_count(text: &str) -> u32 {
+    (text.len() as f32 / 4.0).ceil() as u32
+}
+
+// Heuristic inverse of `heuristic_token_count`
+fn truncate_chars(text: &str, max_tokens: usize) -> String {
+    text.chars().take(max_tokens * 4).collect()
+}
+
+fn bpe_for_encoding(name: &str) -> Option<&'static CoreBPE> {
+    match name {
+        "o200k_base" => Some(tiktoken_rs::o200k_base_singleton()),
+        "cl100k_base" => Some(tiktoken_rs::cl100k_base_singleton()),
+        "p50k_base" => Some(tiktoken_rs::p50k_base_singleton()),
+        "p50k_edit" => Some(tiktoken_rs::p50k_edit_singleton()),
+        "r50k_base" => Some(tiktoken_rs::r50k_base_singleton()),
+        _ => None,
+    }
+}
+
+fn bpe_for_model(model: &str) -> Option<&'static CoreBPE> {
+    use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer as Encoding};
+
+    let name = base_model_name(model);
+
+    let encoding = get_tokenizer(name).or_else(|| {
+        // Newer OpenAI families not yet known to tiktoken-rs all use o200k
+        const O200K_PREFIXES: &[&str] = &["gpt-5", "gpt-4.5", "gpt-oss", "o4", "o3", "o1"];
+        O200K_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+            .then_some(Encoding::O200kBase)
+    })?;
+
+    match encoding {
+        Encoding::O200kBase => Some(tiktoken_rs::o200k_base_singleton()),
+        Encoding::Cl100kBase => Some(tiktoken_rs::cl100k_base_singleton()),
+        Encoding::P50kBase => Some(tiktoken_rs::p50k_base_singleton()),
+        Encoding::P50kEdit => Some(tiktoken_rs::p50k_edit_singleton()),
+        Encoding::R50kBase | Encoding::Gpt2 => Some(tiktoken_rs::r50k_base_singleton()),
+    }
+}
+
+// Selects a tokenizer per model: explicit `--tokenizer MODEL=SPEC` overrides first
+// (SPEC is a tiktoken encoding name or a path to a `tokenizer.json`), then the
+// built-in OpenAI family mapping. Models with neither fall back to the heuristic.
+pub struct TokenizerRegistry {
+    overrides: HashMap<String, Arc<Tokenizer>>,
+    resolved: Mutex<HashMap<String, Option<Arc<Tokenizer>>>>,
+}
+
+impl TokenizerRegistry {
+    pub fn new(overrides: Vec<(String, PathBuf)>) -> Result<Self> {
+        let mut loaded = HashMap::new();
+        for (model, spec) in overrides {
+            let tokenizer = Tokenizer::from_spec(&spec.to_string_lossy())?;
+            loaded.insert(model, Arc::new(tokenizer));
+        }
+
+        Ok(Self {
+            overrides: loaded,
+            resolved: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn for_model(&self, model: &str) -> Option<Arc<Tokenizer>> {
+        if let Some(tokenizer) = self.overrides.get(model) {
+            return Some(Arc::clone(tokenizer));
+        }
+
+        let mut resolved = self.resolved.lock().unwrap();
+        resolved
+            .entry(model.to_string())
+            .or_insert_with(|| bpe_for_model(model).map(|bpe| Arc::new(Tokenizer::Bpe(bpe))))
+            .clone()
+    }
+
+    // Count tokens locally, preferring a real tokenizer over the character heuristic.
+    pub fn count(&self, model: &str, text: &str) -> (u32, TokenSource) {
+        match self.for_model(model).and_then(|tokenizer| tokenizer.count(text)) {
+            Some(count) => (count, TokenSource::Tokenizer),
+            None => (heuristic_token_count(text), TokenSource::Heuristic),
+        }
+    }
+
+    pub fn truncate(&self, model: &str, text: &str, max_tokens: usize) -> String {
+        match self.for_model(model) {
+            Some(tokenizer) => tokenizer.truncate(text, max_tokens),
+            None => truncate_chars(text, max_tokens),
+        }
+    }
+
+    // Count prompt tokens the way OpenAI frames chat messages: 3 tokens per message,
+    // plus the role, plus 3 tokens priming the assistant reply. Other providers use
+    // different templates, so for them this is an approximation.
+    pub fn count_prompt(&self, model: &str, messages: &[ChatMessage]) -> (u32, TokenSource) {
+        const TOKENS_PER_MESSAGE: u32 = 3;
+        const REPLY_PRIMING_TOKENS: u32 = 3;
+
+        let mut total = REPLY_PRIMING_TOKENS;
+        let mut source = TokenSource::Heuristic;
+        for message in messages {
+            let (role, _) = self.count(model, &message.role);
+            let (content, content_source) = self.count(model, message.text());
+            total += TOKENS_PER_MESSAGE + role + content;
+            source = content_source;
+        }
+        (total, source)
+    }
+}
+
+// Parses `MODEL=SPEC` pairs given on the command line.
+pub fn parse_tokenizer_override(value: &str) -> Result<(String, PathBuf), String> {
+    let (model, spec) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODEL=TOKENIZER, got '{}'", value))?;
+    if model.is_empty() || spec.is_empty() {
+        return Err(format!("expected MODEL=TOKENIZER, got '{}'", value));
+    }
+    Ok((model.to_string(), PathBuf::from(spec)))
+}
@@ -0,0 +1,142 @@
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::metrics::{mean_and_percentile, ms, GoodputStats};
+use crate::models::{ChatCompletionRequest, ChatMessage, Tool, ToolCall, ToolChoice};
+use crate::schema;
+
+const TOOL_PROMPT: &str = "What's the weather like in Paris right now, in celsius? Use the get_weather tool.";
+
+pub fn weather_tool() -> Tool {
+    Tool::function(
+        "get_weather",
+        "Get the current weather for a city",
+        json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string", "description": "City name, e.g. Paris" },
+                "unit": { "type": "string", "enum": ["celsius", "fahrenheit"] }
+            },
+            "required": ["city"],
+            "additionalProperties": false
+        }),
+    )
+}
+
+pub fn tool_request(model: &str, tool_choice: &str) -> ChatCompletionRequest {
+    let mut request = ChatCompletionRequest::simple_text_request(model, TOOL_PROMPT, true);
+    request.tools = Some(vec![weather_tool()]);
+    request.tool_choice = Some(ToolChoice::Mode(tool_choice.to_string()));
+    request
+}
+
+// The second leg of the round trip: the original question, the model's tool calls, and a
+// canned result for each, asking the model to answer from them.
+pub fn tool_result_request(first: &ChatCompletionRequest, tool_calls: &[ToolCall]) -> ChatCompletionRequest {
+    let mut request = first.clone();
+    request.tool_choice = None;
+    request.messages.push(ChatMessage::assistant_tool_calls(tool_calls.to_vec()));
+    for call in tool_calls {
+        let result = json!({ "city": "Paris", "temperature": 18, "unit": "celsius", "conditions": "partly cloudy" });
+        request.messages.push(ChatMessage::tool_result(&call.id, &result.to_string()));
+    }
+    request
+}
+
+// The emitted arguments must parse as JSON and conform to the tool's parameter schema.
+pub fn arguments_valid(call: &ToolCall, tool: &Tool) -> bool {
+    call.function.name == tool.function.name
+        && serde_json::from_str::<Value>(&call.function.arguments)
+            .is_ok_and(|arguments| schema::validate(&arguments, &tool.function.parameters).is_ok())
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolCallSample {
+    pub called_tool: bool,
+    pub valid_arguments: bool,
+    pub time_to_first_tool_call: Option<Duration>,
+    pub time_to_arguments_complete: Option<Duration>,
+    pub first_leg_duration: Duration,
+    // Second leg, after feeding the tool result back
+    pub followup_ttft: Option<Duration>,
+    pub followup_duration: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct ToolCallStats {
+    pub model: String,
+    // Attempted, including failed requests
+    pub requests: usize,
+    pub failed_requests: usize,
+    // Of the requests that got a response
+    pub tool_call_rate: f64,
+    pub valid_arguments_rate: f64,
+    pub mean_time_to_first_tool_call: Duration,
+    pub p95_time_to_first_tool_call: Duration,
+    pub mean_time_to_arguments_complete: Duration,
+    pub p95_time_to_arguments_complete: Duration,
+    pub mean_followup_ttft: Duration,
+    pub mean_round_trip: Duration,
+    pub p95_round_trip: Duration,
+    pub goodput: Option<GoodputStats>,
+}
+
+impl ToolCallStats {
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("requests", self.requests as f64),
+            ("failed_requests", self.failed_requests as f64),
+            ("tool_call_rate", self.tool_call_rate),
+            ("valid_arguments_rate", self.valid_arguments_rate),
+            ("mean_time_to_first_tool_call", ms(self.mean_time_to_first_tool_call)),
+            ("p95_time_to_first_tool_call", ms(self.p95_time_to_first_tool_call)),
+            ("mean_time_to_arguments_complete", ms(self.mean_time_to_arguments_complete)),
+            ("p95_time_to_arguments_complete", ms(self.p95_time_to_arguments_complete)),
+            ("mean_followup_ttft", ms(self.mean_followup_ttft)),
+            ("mean_round_trip", ms(self.mean_round_trip)),
+            ("p95_round_trip", ms(self.p95_round_trip)),
+        ]
+        .into_iter()
+        .chain(self.goodput.iter().flat_map(GoodputStats::history_metrics))
+        .collect()
+    }
+}
+
+pub fn summarize(model: &str, samples: &[ToolCallSample], failed_requests: usize, goodput: Option<GoodputStats>) -> ToolCallStats {
+    let n = samples.len().max(1) as f64;
+    let called: Vec<&ToolCallSample> = samples.iter().filter(|s| s.called_tool).collect();
+
+    let (mean_time_to_first_tool_call, p95_time_to_first_tool_call) =
+        mean_and_percentile(called.iter().filter_map(|s| s.time_to_first_tool_call).collect(), 95);
+    let (mean_time_to_arguments_complete, p95_time_to_arguments_complete) =
+        mean_and_percentile(called.iter().filter_map(|s| s.time_to_arguments_complete).collect(), 95);
+    let (mean_followup_ttft, _) =
+        mean_and_percentile(called.iter().filter_map(|s| s.followup_ttft).collect(), 95);
+    let (mean_round_trip, p95_round_trip) = mean_and_percentile(
+        called
+            .iter()
+            .filter_map(|s| s.followup_duration.map(|d| s.first_leg_duration + d))
+            .collect(),
+        95,
+    );
+
+    ToolCallStats {
+        model: model.to_string(),
+        requests: samples.len() + failed_requests,
+        failed_requests,
+        tool_call_rate: called.len() as f64 / n * 100.0,
+        valid_arguments_rate: if called.is_empty() {
+            0.0
+        } else {
+            called.iter().filter(|s| s.valid_arguments).count() as f64 / called.len() as f64 * 100.0
+        },
+        mean_time_to_first_tool_call,
+        p95_time_to_first_tool_call,
+        mean_time_to_arguments_complete,
+        p95_time_to_arguments_complete,
+        mean_followup_ttft,
+        mean_round_trip,
+        p95_round_trip,
+        goodput,
+    }
+}
@@ -0,0 +1,234 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use rand::Rng;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::metrics::{linear_fit, mean_duration, ms, percentile, weighted_mean, GoodputStats, LinearFit};
+use crate::models::{ChatCompletionRequest, ChatMessage, StreamOptions};
+
+const VISION_PROMPT: &str = "Describe the image(s) in one short sentence.";
+// Largest synthetic image side; its raw pixels take about 200 MB
+const MAX_SYNTHETIC_SIDE: u32 = 8192;
+
+// An image ready to send inline as a base64 data URL
+#[derive(Debug, Clone)]
+pub struct ImageInput {
+    pub label: String,
+    pub width: u32,
+    pub height: u32,
+    pub data_url: String,
+}
+
+impl ImageInput {
+    pub fn megapixels(&self) -> f64 {
+        self.width as f64 * self.height as f64 / 1_000_000.0
+    }
+}
+
+pub fn load_image(path: &Path) -> Result<ImageInput> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read image {}", path.display()))?;
+    let size = imagesize::blob_size(&bytes)
+        .map_err(|e| anyhow::anyhow!("unsupported image {}: {:?}", path.display(), e))?;
+    let mime = match imagesize::image_type(&bytes) {
+        Ok(imagesize::ImageType::Png) => "image/png",
+        Ok(imagesize::ImageType::Jpeg) => "image/jpeg",
+        Ok(imagesize::ImageType::Gif) => "image/gif",
+        Ok(imagesize::ImageType::Webp) => "image/webp",
+        other => return Err(anyhow::anyhow!("unsupported image format for {}: {:?}", path.display(), other)),
+    };
+
+    Ok(ImageInput {
+        label: path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().to_string()),
+        width: size.width as u32,
+        height: size.height as u32,
+        data_url: data_url(mime, &bytes),
+    })
+}
+
+// Parses a synthetic image side length given on the command line
+pub fn parse_resolution(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(side) if (1..=MAX_SYNTHETIC_SIDE).contains(&side) => Ok(side),
+        _ => Err(format!("expected a side length from 1 to {} pixels, got '{}'", MAX_SYNTHETIC_SIDE, value)),
+    }
+}
+
+// A square PNG of `side` pixels. The pattern is smooth so the payload stays small even at
+// high resolutions, with random colours so no two images are byte-identical.
+pub fn synthetic_image(side: u32) -> Result<ImageInput> {
+    let mut rng = rand::rng();
+    let tint: [u32; 3] = [rng.random_range(0..256), rng.random_range(0..256), rng.random_range(0..256)];
+    let cell = (side / 8).max(1);
+
+    let mut pixels = Vec::with_capacity(side as usize * side as usize * 3);
+    for y in 0..side {
+        for x in 0..side {
+            let checker = if (x / cell + y / cell).is_multiple_of(2) { 0 } else { 96 };
+            pixels.push(((x * 255 / side + tint[0] + checker) % 256) as u8);
+            pixels.push(((y * 255 / side + tint[1]) % 256) as u8);
+            pixels.push(((tint[2] + checker) % 256) as u8);
+        }
+    }
+
+    let mut encoded = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut encoded, side, side);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+    }
+
+    Ok(ImageInput {
+        label: ImageSource::Synthetic(side).label(),
+        width: side,
+        height: side,
+        data_url: data_url("image/png", &encoded),
+    })
+}
+
+fn data_url(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+// Where a case's images come from. Synthetic images are regenerated for every request so
+// repeated requests can't be answered from a provider-side image cache.
+#[derive(Debug, Clone)]
+pub enum ImageSource {
+    File(ImageInput),
+    Synthetic(u32),
+}
+
+impl ImageSource {
+    pub fn label(&self) -> String {
+        match self {
+            ImageSource::File(image) => image.label.clone(),
+            ImageSource::Synthetic(side) => format!("synthetic {}x{}", side, side),
+        }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            ImageSource::File(image) => (image.width, image.height),
+            ImageSource::Synthetic(side) => (*side, *side),
+        }
+    }
+
+    pub fn images(&self, count: usize) -> Result<Vec<ImageInput>> {
+        match self {
+            ImageSource::File(image) => Ok(vec![image.clone(); count]),
+            ImageSource::Synthetic(side) => (0..count).map(|_| synthetic_image(*side)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VisionCase {
+    pub source: ImageSource,
+    pub image_count: usize,
+}
+
+pub fn vision_request(model: &str, images: &[ImageInput]) -> ChatCompletionRequest {
+    let urls: Vec<String> = images.iter().map(|image| image.data_url.clone()).collect();
+    let mut request = ChatCompletionRequest::conversation_request(
+        model,
+        vec![ChatMessage::user_with_images(VISION_PROMPT, &urls)],
+        true,
+    );
+    request.max_completion_tokens = Some(16);
+    request.stream_options = Some(StreamOptions { include_usage: true });
+    request
+}
+
+#[derive(Debug, Clone)]
+pub struct VisionSample {
+    pub case: usize,
+    // Total across all images in the request
+    pub megapixels: f64,
+    pub payload_bytes: usize,
+    pub time_to_first_token: Duration,
+    pub prompt_tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VisionPoint {
+    pub label: String,
+    pub width: u32,
+    pub height: u32,
+    pub image_count: usize,
+    pub successful_requests: usize,
+    pub failed_requests: usize,
+    pub payload_bytes: usize,
+    pub mean_ttft: Duration,
+    pub p50_ttft: Duration,
+    // None when the provider didn't report usage
+    pub mean_prompt_tokens: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct VisionStats {
+    pub model: String,
+    pub points: Vec<VisionPoint>,
+    // TTFT (ms) against total megapixels per request
+    pub fit: Option<LinearFit>,
+    pub goodput: Option<GoodputStats>,
+}
+
+impl VisionStats {
+    // Totals over every image case, plus the TTFT-per-megapixel fit
+    pub fn history_metrics(&self) -> Vec<(&'static str, f64)> {
+        let mut metrics = vec![
+            ("requests", self.points.iter().map(|p| p.successful_requests + p.failed_requests).sum::<usize>() as f64),
+            ("failed_requests", self.points.iter().map(|p| p.failed_requests).sum::<usize>() as f64),
+            ("mean_ttft", weighted_mean(self.points.iter().map(|p| (ms(p.mean_ttft), p.successful_requests)))),
+        ];
+        if let Some(fit) = &self.fit {
+            metrics.push(("ttft_ms_per_megapixel", fit.slope));
+            metrics.push(("fit_r_squared", fit.r_squared));
+        }
+        metrics.extend(self.goodput.iter().flat_map(GoodputStats::history_metrics));
+        metrics
+    }
+}
+
+pub fn summarize(model: &str, cases: &[VisionCase], samples: &[VisionSample], failures: &[usize], goodput: Option<GoodputStats>) -> VisionStats {
+    let points = cases
+        .iter()
+        .enumerate()
+        .map(|(index, case)| {
+            let case_samples: Vec<&VisionSample> = samples.iter().filter(|s| s.case == index).collect();
+            let mut ttfts: Vec<Duration> = case_samples.iter().map(|s| s.time_to_first_token).collect();
+            ttfts.sort();
+            let prompt_tokens: Vec<u32> = case_samples.iter().filter_map(|s| s.prompt_tokens).collect();
+            let (width, height) = case.source.dimensions();
+            let n = ttfts.len();
+
+            VisionPoint {
+                label: case.source.label(),
+                width,
+                height,
+                image_count: case.image_count,
+                successful_requests: n,
+                failed_requests: failures.iter().filter(|&&f| f == index).count(),
+                payload_bytes: case_samples.first().map_or(0, |s| s.payload_bytes),
+                mean_ttft: mean_duration(&ttfts),
+                p50_ttft: percentile(&ttfts, 50),
+                mean_prompt_tokens: (!prompt_tokens.is_empty())
+                    .then(|| prompt_tokens.iter().sum::<u32>() / prompt_tokens.len() as u32),
+            }
+        })
+        .collect();
+
+    let fit_points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|s| (s.megapixels, s.time_to_first_token.as_secs_f64() * 1000.0))
+        .collect();
+
+    VisionStats {
+        model: model.to_string(),
+        points,
+        fit: linear_fit(&fit_points),
+        goodput,
+    }
+}